@@ -1,8 +1,54 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use time_series_data_packer_rs::*;
 
 const WINDOW_US: u64 = 1_000;
 
+/// System allocator that counts allocated bytes and the peak of live bytes,
+/// so the pipeline benchmarks can report memory next to time.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+            let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Bytes allocated by `run` in total and at its peak, above what was live
+/// before it started.
+fn measure_allocations<T>(run: impl FnOnce() -> T) -> (usize, usize) {
+    let live_before = LIVE.load(Ordering::Relaxed);
+    let allocated_before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(live_before, Ordering::Relaxed);
+
+    black_box(run());
+
+    (
+        ALLOCATED.load(Ordering::Relaxed) - allocated_before,
+        PEAK.load(Ordering::Relaxed) - live_before,
+    )
+}
+
 fn make_constant_samples(size: usize) -> Vec<TSSamples> {
     (0..size).map(|i| (i as f64 * 0.001, 100.0)).collect()
 }
@@ -20,7 +66,8 @@ fn pack_with_strategy(samples: &[TSSamples], strategy: TSPackStrategyType) -> Ve
         precision_epsilon: TSPackPrecisionDataType::IoTSensors.epsilon(),
     };
     let mut packer = TimeSeriesDataPacker::new();
-    packer.pack(samples.to_vec(), attrs).unwrap()
+    packer.pack(samples.to_vec(), attrs).unwrap();
+    packer.into_packed_samples()
 }
 
 fn benchmark_pack_strategies(c: &mut Criterion) {
@@ -231,8 +278,142 @@ fn benchmark_delta_of_delta_accelerating(c: &mut Criterion) {
     }
 }
 
+fn benchmark_owned_vs_borrowed_pipeline(c: &mut Criterion) {
+    let sizes = [100_000, 1_000_000];
+    let attrs = TSPackAttributes {
+        strategy_types: vec![TSPackStrategyType::TSPackXorStrategy],
+        microseconds_time_window: WINDOW_US,
+        precision_epsilon: 0.0,
+    };
+
+    for size in sizes {
+        let samples = make_incremental_samples(size);
+        let mut group = c.benchmark_group(format!("pipeline_xor_{size}"));
+        group.throughput(Throughput::Elements(size as u64));
+        group.sample_size(10);
+
+        // copies the input into an owned Vec
+        let pack_owned = || {
+            let mut packer = TimeSeriesDataPacker::new();
+            packer
+                .pack(black_box(&samples).to_vec(), attrs.clone())
+                .unwrap();
+            packer.into_packed_samples()
+        };
+
+        // borrows the sorted input and windows
+        let pack_slice = || {
+            let mut packer = TimeSeriesDataPacker::new();
+            packer
+                .pack_slice(black_box(&samples), attrs.clone())
+                .unwrap();
+            packer.into_packed_samples()
+        };
+
+        for (name, (allocated, peak)) in [
+            ("pack_owned", measure_allocations(pack_owned)),
+            ("pack_slice", measure_allocations(pack_slice)),
+        ] {
+            println!("pipeline_xor_{size}/{name}: {allocated} bytes allocated, {peak} bytes peak");
+        }
+
+        group.bench_function("pack_owned", |b| b.iter(|| black_box(pack_owned())));
+        group.bench_function("pack_slice", |b| b.iter(|| black_box(pack_slice())));
+
+        group.finish();
+    }
+}
+
+fn benchmark_query_range(c: &mut Criterion) {
+    let size = 1_000_000;
+    let samples = make_incremental_samples(size);
+    let last_ts = samples[size - 1].0;
+    // the most recent 1% of the series
+    let start = last_ts - (last_ts - samples[0].0) / 100.0;
+
+    let strategies = [
+        ("xor", TSPackStrategyType::TSPackXorStrategy),
+        ("run_length", TSPackStrategyType::TSPackRunLengthStrategy),
+    ];
+
+    let mut group = c.benchmark_group(format!("query_range_{size}"));
+    group.sample_size(10);
+
+    for (name, strategy) in strategies {
+        let mut packer = TimeSeriesDataPacker::new();
+        packer
+            .pack_slice(
+                &samples,
+                TSPackAttributes {
+                    strategy_types: vec![strategy],
+                    microseconds_time_window: WINDOW_US,
+                    precision_epsilon: 0.0,
+                },
+            )
+            .unwrap();
+
+        group.bench_function(format!("{name}_decode_and_filter"), |b| {
+            b.iter(|| {
+                let decoded = packer.decode();
+                black_box(
+                    decoded
+                        .into_iter()
+                        .filter(|(ts, _)| *ts >= start && *ts <= last_ts)
+                        .count(),
+                )
+            })
+        });
+
+        group.bench_function(format!("{name}_query_range"), |b| {
+            b.iter(|| black_box(packer.query_range(black_box(start), last_ts).len()))
+        });
+    }
+
+    group.finish();
+}
+
+#[cfg(feature = "parallel")]
+fn benchmark_parallel_windows(c: &mut Criterion) {
+    let sizes = [100_000, 1_000_000];
+    let attrs = TSPackAttributes {
+        strategy_types: vec![TSPackStrategyType::TSPackAutoStrategy {
+            candidates: time_series_data_packer_rs::strategies::auto::default_auto_candidates(),
+            max_error: 0.01,
+        }],
+        microseconds_time_window: 100_000,
+        precision_epsilon: 0.001,
+    };
+
+    for size in sizes {
+        let samples = make_incremental_samples(size);
+        let mut group = c.benchmark_group(format!("parallel_windows_auto_{size}"));
+        group.throughput(Throughput::Elements(size as u64));
+        group.sample_size(10);
+
+        for (name, parallel) in [("sequential", false), ("parallel", true)] {
+            group.bench_function(name, |b| {
+                b.iter(|| {
+                    let mut packer = TimeSeriesDataPacker::new().with_parallel_windows(parallel);
+                    packer
+                        .pack_slice(black_box(&samples), attrs.clone())
+                        .unwrap();
+                    black_box(packer.into_packed_samples())
+                })
+            });
+        }
+
+        group.finish();
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+fn benchmark_parallel_windows(_c: &mut Criterion) {}
+
 criterion_group!(
     benches,
+    benchmark_parallel_windows,
+    benchmark_query_range,
+    benchmark_owned_vs_borrowed_pipeline,
     benchmark_pack_strategies,
     benchmark_delta_of_delta_accelerating,
     benchmark_xor_gorilla_incremental,
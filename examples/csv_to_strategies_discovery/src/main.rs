@@ -6,10 +6,11 @@ use csv::ReaderBuilder;
 use std::io::BufWriter;
 
 
+use time_series_data_packer_rs::metrics::compute_error_metrics;
 use time_series_data_packer_rs::{
     TimeSeriesDataPacker,
+    TSPackAlignment,
     TSPackAttributes,
-    TSPackStrategyType,
     TSSamples,
     TSPackedSamples,
 };
@@ -488,61 +489,24 @@ let samples: Vec<TSSamples> = vec![
 }
 
 fn build_strategies(time_window_us: u64) -> Vec<(String, TSPackAttributes)> {
-    vec![
-        (
-            "similar".to_string(),
-            TSPackAttributes {
-                strategy_types: vec![TSPackStrategyType::TSPackSimilarValuesStrategy],
-                microseconds_time_window: time_window_us,
-                precision_epsilon: 0.01,
-            },
-        ),
-        (
-            "mean_1pct".to_string(),
-            TSPackAttributes {
-                strategy_types: vec![TSPackStrategyType::TSPackMeanStrategy { values_compression_percent: 1 }],
-                microseconds_time_window: time_window_us,
-                precision_epsilon: 0.0001,
-            },
-        ),
-        (
-            "mean_5pct".to_string(),
-            TSPackAttributes {
-                strategy_types: vec![TSPackStrategyType::TSPackMeanStrategy { values_compression_percent: 5 }],
-                microseconds_time_window: time_window_us,
-                precision_epsilon: 0.0001,
-            },
-        ),
-        (
-            "mean_10pct".to_string(),
-            TSPackAttributes {
-                strategy_types: vec![TSPackStrategyType::TSPackMeanStrategy { values_compression_percent: 10 }],
-                microseconds_time_window: time_window_us,
-                precision_epsilon: 0.0001,
-            },
-        ),
-        (
-            "mean_30pct".to_string(),
-            TSPackAttributes {
-                strategy_types: vec![TSPackStrategyType::TSPackMeanStrategy { values_compression_percent: 30 }],
-                microseconds_time_window: time_window_us,
-                precision_epsilon: 0.0001,
-            },
-        ),
-        (
-            //"delta_next_mean_next_similarvalues_30pct".to_string(),
-            "delta_next_similarvalues".to_string(),
-            TSPackAttributes {
-                strategy_types: vec![
-                    TSPackStrategyType::TSPackDeltaStrategy,
-                    TSPackStrategyType::TSPackSimilarValuesStrategy,
-                    //TSPackStrategyType::TSPackMeanStrategy { values_compression_percent: 30 },
-                ],
-                microseconds_time_window: time_window_us,
-                precision_epsilon: 0.0001,
-            },
-        ),
+    [
+        ("similar", "eps=0.01;similar"),
+        ("mean_1pct", "eps=0.0001;mean(1)"),
+        ("mean_5pct", "eps=0.0001;mean(5)"),
+        ("mean_10pct", "eps=0.0001;mean(10)"),
+        ("mean_30pct", "eps=0.0001;mean(30)"),
+        // a bit-exact encoding has to end the chain, so similar values go first
+        ("similarvalues_next_delta", "eps=0.0001;similar|delta"),
+        ("auto_err_0.01", "eps=0.0001;auto(0.01)"),
     ]
+    .into_iter()
+    .map(|(name, spec)| {
+        let attributes = format!("window={time_window_us}us;{spec}")
+            .parse()
+            .expect("valid pipeline spec");
+        (name.to_string(), attributes)
+    })
+    .collect()
 }
 
 fn run_strategy(
@@ -551,40 +515,10 @@ fn run_strategy(
     attributes: TSPackAttributes,
 ) -> (String, Vec<TSPackedSamples>) {
     let mut packer = TimeSeriesDataPacker::new();
-    let packed = packer.pack(samples.to_vec(), attributes);
-    (name.to_string(), packed.expect("Name need to be string"))
-}
-
-fn compression_ratio(original_count: usize, packed_count: usize) -> f64 {
-    original_count as f64 / packed_count as f64
-}
-
-fn compute_error_metrics(
-    original: &[TSSamples],
-    reconstructed: &[TSSamples],
-) -> (f64, f64, f64, f64, f64, f64) {
-    //assert_eq!(original.len(), reconstructed.len());
-
-    let mut sum_abs = 0.0;
-    let mut sum_sq = 0.0;
-    let mut max_err = 0.0;
-    let mut rn = 0.0;
-
-    for (o, r) in original.iter().zip(reconstructed.iter()) {
-        let err = (o.1 - r.1).abs();
-        sum_abs += err;
-        sum_sq += err * err;
-        if err > max_err {
-            max_err = err;
-        }
-        rn += 1.0;
-    }
-
-    let n = original.len() as f64;
-    let mae = sum_abs / n;
-    let mse = sum_sq / n;
-    let l_rn = n / rn;
-    (mae, mse, max_err, l_rn, n, rn)
+    packer
+        .pack(samples.to_vec(), attributes)
+        .expect("Name need to be string");
+    (name.to_string(), packer.into_packed_samples())
 }
 
 fn save_rs_txt(
@@ -615,7 +549,7 @@ fn save_rs_txt(
 
 pub fn save_rs_txt_packed(
     path: &str,
-    packed: &Vec<((f64, f64), f64)>,
+    packed: &[((f64, f64), f64)],
     precision: usize,
     scale: f64,
 ) -> std::io::Result<()> {
@@ -644,7 +578,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let args: Vec<String> = env::args().collect();
     if args.len() < 3 {
-        eprintln!("Usage: csv_to_strategies_discovery <input.csv> <time_window_us>");
+        eprintln!("Usage: csv_to_strategies_discovery <input.csv> <time_window_us> [pipeline spec, e.g. \"eps=1e-3;similar|xor\"]");
         std::process::exit(1);
     }
 
@@ -654,24 +588,29 @@ fn main() -> Result<(), Box<dyn Error>> {
     //let samples = load_csv_as_ts_samples(input_csv)?;
     let samples = load_example_manual_create_dataset()?;
 
-    let strategies = build_strategies(time_window_us);
+    let strategies = match args.get(3) {
+        Some(spec) => vec![(
+            "custom".to_string(),
+            format!("window={time_window_us}us;{spec}").parse::<TSPackAttributes>()?,
+        )],
+        None => build_strategies(time_window_us),
+    };
 
     let _ = save_rs_txt(&("samples.origin.rs.txt"), &samples, precision, 1.0);
 
     for (name, attrs) in strategies {
         let mut packer = TimeSeriesDataPacker::new();
-        let mut packed = packer.pack(samples.clone(), attrs.clone());
-
-        let packed_tmp = packer.pack(samples.clone(), attrs.clone())?;
-        save_rs_txt_packed(&(name.clone() + ".rs.txt.packed"), &packed_tmp, precision, 1.0)?;
+        let packed = packer.pack(samples.clone(), attrs.clone())?;
+        save_rs_txt_packed(&(name.clone() + ".rs.txt.packed"), packed, precision, 1.0)?;
 
-        let ratio = compression_ratio(samples.len(), packed?.len());
+        let ratio = packer.size_stats().ratio();
 
-        let (_attrs_back, reconstructed) = packer.unpack();
-        let (mae, mse, max_err, l_rn, n, rn) = compute_error_metrics(&samples, &reconstructed);
+        let reconstructed = packer.decode();
+        let metrics = compute_error_metrics(&samples, &reconstructed, TSPackAlignment::StepHold);
 
         println!(
-            "strategy={name:10} ratio={ratio:8.3} mae={mae:12.6} mse={mse:12.6} max_err={max_err:12.6} l_rn={l_rn:12.6} n={n:12.6} rn={rn:12.6}"
+            "strategy={name:10} ratio={ratio:8.3} mae={:12.6} mse={:12.6} rmse={:12.6} max_err={:12.6} snr_db={:10.3} nrmse={:10.6}",
+            metrics.mae, metrics.mse, metrics.rmse, metrics.max_error, metrics.snr_db, metrics.nrmse
         );
         
         let _ = save_rs_txt(&(name + ".rs.txt.unpacked"), &reconstructed, precision, 1.0);
@@ -256,4 +256,5 @@ fn pack(report: &mut DemoReport, samples: Vec<TSSamples>, attrs: TSPackAttribute
         .packer
         .pack(samples, attrs)
         .expect("pack should succeed")
+        .to_vec()
 }
@@ -1,4 +1,4 @@
-use time_series_data_packer_rs::{TSPackedSamples, TSSamples, TimeSeriesDataPacker};
+use time_series_data_packer_rs::{TSPackSizeStats, TSPackedSamples, TSSamples, TimeSeriesDataPacker};
 
 pub struct DemoReport {
     pub packer: TimeSeriesDataPacker,
@@ -44,20 +44,22 @@ impl DemoReport {
     }
 
     pub fn print_summary(&self, packed: &[TSPackedSamples]) {
-        let ratio = if packed.is_empty() {
-            0.0
-        } else {
-            self.raw_count as f64 / packed.len() as f64
-        };
+        let stats = TSPackSizeStats::new(self.raw_count, packed);
 
         println!("Strategy:     {}", self.strategy_name);
         println!("Dataset:      {}", self.description);
         println!("Source file:  {}", self.data_file);
-        println!("Raw samples:  {}", self.raw_count);
+        println!("Raw samples:  {} ({} bytes)", self.raw_count, stats.raw_bytes());
         println!("Time span:    {:.3} s", self.time_span_s);
         println!("Value range:  {:.4} … {:.4}", self.value_min, self.value_max);
-        println!("Packed entries: {}", packed.len());
-        println!("Compression:  {:.1}x fewer entries ({:.1}%)", ratio, 100.0 / ratio.max(1.0));
+        println!("Packed entries: {} ({} bytes)", packed.len(), stats.encoded_bytes());
+        println!(
+            "Compression:  {:.2}x by bytes ({:.1} bits/sample; timestamps {:.2}x, values {:.2}x)",
+            stats.ratio(),
+            stats.bits_per_sample(),
+            stats.timestamp_ratio(),
+            stats.value_ratio()
+        );
     }
 
     pub fn print_packed_preview(&self, packed: &[TSPackedSamples], limit: usize) {
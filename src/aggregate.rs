@@ -0,0 +1,289 @@
+use crate::TSPackError;
+use crate::TSPackedSamples;
+use crate::TimeSeriesDataPacker;
+
+/// Aggregates of the packed series over `[start, end]`.
+///
+/// `count`, `sum` and `mean` cover the samples [`TimeSeriesDataPacker::decode`]
+/// returns inside the range (a packed range contributes its start and end
+/// point). `min`, `max`, `duration` and `time_weighted_mean` follow step-hold:
+/// every entry holds its value until the next one starts, the last one until
+/// its own end, so a value held into the range counts even without a sample
+/// inside it. `time_weighted_mean` equals `mean` when `duration` is 0.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TSPackAggregate {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+    pub sum: f64,
+    pub mean: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Seconds of `[start, end]` covered by held values.
+    pub duration: f64,
+    pub time_weighted_mean: Option<f64>,
+}
+
+struct Accumulator {
+    aggregate: TSPackAggregate,
+    // whether a sample exactly at `end` belongs to this range
+    closed: bool,
+    integral: f64,
+}
+
+impl Accumulator {
+    fn new(start: f64, end: f64, closed: bool) -> Self {
+        Self {
+            aggregate: TSPackAggregate {
+                start,
+                end,
+                ..TSPackAggregate::default()
+            },
+            closed,
+            integral: 0.0,
+        }
+    }
+
+    fn contains(&self, ts: f64) -> bool {
+        ts >= self.aggregate.start
+            && (ts < self.aggregate.end || (self.closed && ts == self.aggregate.end))
+    }
+
+    fn observe(&mut self, value: f64) {
+        let aggregate = &mut self.aggregate;
+        aggregate.min = Some(aggregate.min.map_or(value, |min| min.min(value)));
+        aggregate.max = Some(aggregate.max.map_or(value, |max| max.max(value)));
+    }
+
+    fn sample(&mut self, ts: f64, value: f64) {
+        if self.contains(ts) {
+            self.aggregate.count += 1;
+            self.aggregate.sum += value;
+            self.observe(value);
+        }
+    }
+
+    /// Adds entries sorted by start, stopping after the first one that starts
+    /// past the range; leading entries that end before it are skipped.
+    fn add(&mut self, entries: impl Iterator<Item = TSPackedSamples>) {
+        let mut entries = entries.peekable();
+
+        while let Some(((start, end), value)) = entries.next() {
+            if start > self.aggregate.end {
+                break;
+            }
+
+            self.sample(start, value);
+            if end != start {
+                self.sample(end, value);
+            }
+
+            let held_until = entries.peek().map(|((next, _), _)| *next).unwrap_or(end);
+            let held = held_until.min(self.aggregate.end) - start.max(self.aggregate.start);
+            if held > 0.0 {
+                self.integral += value * held;
+                self.aggregate.duration += held;
+                self.observe(value);
+            }
+        }
+    }
+
+    fn finish(mut self) -> TSPackAggregate {
+        let aggregate = &mut self.aggregate;
+
+        if aggregate.count > 0 {
+            aggregate.mean = Some(aggregate.sum / aggregate.count as f64);
+        }
+
+        aggregate.time_weighted_mean = if aggregate.duration > 0.0 {
+            Some(self.integral / aggregate.duration)
+        } else {
+            aggregate.mean
+        };
+
+        self.aggregate
+    }
+}
+
+impl TimeSeriesDataPacker {
+    /// Count, sum, mean, min, max, duration and time-weighted mean over
+    /// `[start, end]` (seconds, inclusive), see [`TSPackAggregate`].
+    ///
+    /// Range chains (Similar Values, Mean, Run-length) are aggregated straight
+    /// from the packed ranges; bit-exact chains decode only the windows
+    /// overlapping the range.
+    pub fn aggregate(&self, start: f64, end: f64) -> TSPackAggregate {
+        let mut accumulator = Accumulator::new(start, end, true);
+        if start <= end {
+            self.accumulate(&mut accumulator);
+        }
+        accumulator.finish()
+    }
+
+    /// [`Self::aggregate`] per bucket of `bucket_microseconds`, starting at
+    /// `start`. Buckets are half-open except the last one, which is clipped
+    /// to `end` and includes it, so no sample is counted twice.
+    pub fn aggregate_buckets(
+        &self,
+        start: f64,
+        end: f64,
+        bucket_microseconds: u64,
+    ) -> Result<Vec<TSPackAggregate>, TSPackError> {
+        if bucket_microseconds == 0 {
+            return Err(TSPackError::InvalidWindow);
+        }
+
+        if start.is_nan() || end.is_nan() || start > end {
+            return Ok(Vec::new());
+        }
+
+        let invalid_range = TSPackError::InvalidTimeRange { start, end };
+
+        if !start.is_finite() || !end.is_finite() {
+            return Err(invalid_range);
+        }
+
+        let bucket_seconds = bucket_microseconds as f64 / 1_000_000.0;
+        let span = ((end - start) / bucket_seconds).ceil();
+
+        if span >= usize::MAX as f64 {
+            return Err(invalid_range);
+        }
+
+        let buckets = (span as usize).max(1);
+        let mut aggregates = Vec::new();
+        aggregates.try_reserve(buckets).map_err(|_| invalid_range)?;
+
+        aggregates.extend((0..buckets).map(|bucket| {
+            let bucket_start = start + bucket as f64 * bucket_seconds;
+            let last = bucket + 1 == buckets;
+            let bucket_end = if last {
+                end
+            } else {
+                (bucket_start + bucket_seconds).min(end)
+            };
+
+            let mut accumulator = Accumulator::new(bucket_start, bucket_end, last);
+            self.accumulate(&mut accumulator);
+            accumulator.finish()
+        }));
+
+        Ok(aggregates)
+    }
+
+    fn accumulate(&self, accumulator: &mut Accumulator) {
+        accumulator.add(self.entries_from(accumulator.aggregate.start));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::auto::default_auto_candidates;
+    use crate::test_support::pack;
+    use crate::{TSPackStrategyType, TSSamples};
+
+    #[test]
+    fn ranges_aggregate_without_decoding() {
+        // ((0, 2), 1.0), ((3, 6), 5.0), ((7, 7), 2.0)
+        let samples = vec![(0.0, 1.0), (2.0, 1.0), (3.0, 5.0), (6.0, 5.0), (7.0, 2.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackRunLengthStrategy);
+
+        let all = packer.aggregate(0.0, 7.0);
+        assert_eq!(all.count, 5);
+        assert_eq!(all.sum, 14.0);
+        assert_eq!(all.mean, Some(2.8));
+        assert_eq!((all.min, all.max), (Some(1.0), Some(5.0)));
+        assert_eq!(all.duration, 7.0);
+        // 1.0 held for 3 s, 5.0 for 4 s
+        assert_eq!(all.time_weighted_mean, Some(23.0 / 7.0));
+
+        // 1.0 is held from 2.0 to 3.0, but only 5.0 has a sample inside
+        let held = packer.aggregate(2.5, 4.0);
+        assert_eq!(held.count, 1);
+        assert_eq!(held.mean, Some(5.0));
+        assert_eq!((held.min, held.max), (Some(1.0), Some(5.0)));
+        assert_eq!(held.time_weighted_mean, Some((0.5 + 5.0) / 1.5));
+
+        let point = packer.aggregate(7.0, 7.0);
+        assert_eq!(point.count, 1);
+        assert_eq!(point.time_weighted_mean, Some(2.0));
+    }
+
+    #[test]
+    fn bit_exact_chains_match_decoded_samples() {
+        let samples: Vec<TSSamples> = (0..100)
+            .map(|i| (i as f64 * 0.1, (i % 9) as f64 * 0.5 - 1.0))
+            .collect();
+
+        let strategies = vec![
+            TSPackStrategyType::TSPackXorStrategy,
+            TSPackStrategyType::TSPackDeltaOfDeltaStrategy,
+            TSPackStrategyType::TSPackSimple8bStrategy,
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates: default_auto_candidates(),
+                max_error: 0.0,
+            },
+        ];
+
+        for strategy in strategies {
+            let packer = pack(samples.clone(), strategy.clone());
+            let inside: Vec<f64> = packer
+                .decode()
+                .into_iter()
+                .filter(|(ts, _)| (2.05..=6.0).contains(ts))
+                .map(|(_, value)| value)
+                .collect();
+
+            let aggregate = packer.aggregate(2.05, 6.0);
+            let sum: f64 = inside.iter().sum();
+
+            assert_eq!(aggregate.count, inside.len(), "{strategy:?}");
+            assert!((aggregate.sum - sum).abs() < 1e-9, "{strategy:?}");
+            assert_eq!(aggregate.min, Some(-1.0), "{strategy:?}");
+            assert_eq!(aggregate.max, Some(3.0), "{strategy:?}");
+            assert!((aggregate.duration - 3.95).abs() < 1e-9, "{strategy:?}");
+        }
+    }
+
+    #[test]
+    fn buckets_split_without_double_counting() {
+        let samples: Vec<TSSamples> = (0..=10).map(|i| (i as f64, i as f64)).collect();
+        let packer = pack(samples, TSPackStrategyType::TSPackXorStrategy);
+
+        let buckets = packer.aggregate_buckets(0.0, 10.0, 4_000_000).unwrap();
+        let counts: Vec<usize> = buckets.iter().map(|bucket| bucket.count).collect();
+        let bounds: Vec<(f64, f64)> = buckets.iter().map(|b| (b.start, b.end)).collect();
+
+        assert_eq!(counts, vec![4, 4, 3]);
+        assert_eq!(bounds, vec![(0.0, 4.0), (4.0, 8.0), (8.0, 10.0)]);
+        assert_eq!(buckets[1].mean, Some(5.5));
+        assert_eq!(buckets[1].time_weighted_mean, Some(5.5));
+
+        assert_eq!(
+            packer.aggregate_buckets(0.0, 10.0, 0),
+            Err(TSPackError::InvalidWindow)
+        );
+        assert_eq!(packer.aggregate_buckets(5.0, 1.0, 1_000_000), Ok(vec![]));
+        assert_eq!(
+            packer.aggregate_buckets(0.0, f64::INFINITY, 1_000_000),
+            Err(TSPackError::InvalidTimeRange {
+                start: 0.0,
+                end: f64::INFINITY
+            })
+        );
+        assert_eq!(
+            packer.aggregate_buckets(f64::NEG_INFINITY, 10.0, 1_000_000),
+            Err(TSPackError::InvalidTimeRange {
+                start: f64::NEG_INFINITY,
+                end: 10.0
+            })
+        );
+
+        let empty = TimeSeriesDataPacker::new().aggregate(0.0, 1.0);
+        assert_eq!(
+            (empty.count, empty.min, empty.time_weighted_mean),
+            (0, None, None)
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::ops::Range;
+
+use crate::decode::decode_window_iter;
+use crate::helpers::round_to_precision;
+use crate::helpers::uses_bit_exact_encoding;
+use crate::TSPackAttributes;
+use crate::TSPackError;
+use crate::TSPackedSamples;
+use crate::TSSamples;
+use crate::TimeSeriesDataPacker;
+
+/// Footer index entry of one block of a [`TSPackBlockContainer`].
+///
+/// `start` and `end` are the first and last decoded timestamps, `offset` and
+/// `entries` locate the block's packed entries and `count` is the number of
+/// samples it decodes to. The rest is the block's zone map: `min`, `max` and
+/// `sum` skip NaN samples (`min` and `max` are NaN when the block holds
+/// nothing else), `first` and `last` are the values at `start` and `end`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSPackBlockIndexEntry {
+    pub start: f64,
+    pub end: f64,
+    pub offset: usize,
+    pub entries: usize,
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    pub first: f64,
+    pub last: f64,
+}
+
+impl TSPackBlockIndexEntry {
+    /// Packed entries of the block.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.entries
+    }
+
+    /// Whether the block holds samples inside `[start, end]`.
+    pub fn overlaps(&self, start: f64, end: f64) -> bool {
+        self.start <= end && self.end >= start
+    }
+}
+
+/// Packed series cut into independently decodable blocks, with a footer
+/// index describing every block.
+///
+/// Blocks of bit-exact chains are the packing windows, each with its own
+/// anchor. Blocks of range chains are the windows after the cross-window
+/// merge; a range merged across windows belongs to the block it starts in.
+/// Readers binary-search the index and decode only the blocks they need.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TSPackBlockContainer {
+    attributes: TSPackAttributes,
+    entries: Vec<TSPackedSamples>,
+    index: Vec<TSPackBlockIndexEntry>,
+}
+
+impl TSPackBlockContainer {
+    pub fn attributes(&self) -> &TSPackAttributes {
+        &self.attributes
+    }
+
+    /// Packed entries of all blocks, back to back.
+    pub fn entries(&self) -> &[TSPackedSamples] {
+        &self.entries
+    }
+
+    /// The footer index, one entry per block in time order.
+    pub fn index(&self) -> &[TSPackBlockIndexEntry] {
+        &self.index
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Blocks that overlap `[start, end]` (seconds, inclusive), found by
+    /// binary search. Empty when `start > end` or either bound is NaN.
+    pub fn seek(&self, start: f64, end: f64) -> Range<usize> {
+        if start.is_nan() || end.is_nan() || start > end {
+            return 0..0;
+        }
+
+        let first = self.index.partition_point(|block| block.end < start);
+        let last = self.index.partition_point(|block| block.start <= end);
+
+        first..last.max(first)
+    }
+
+    /// Packed entries of `block`, `None` past the last block.
+    pub fn block_entries(&self, block: usize) -> Option<&[TSPackedSamples]> {
+        self.index
+            .get(block)
+            .map(|block| &self.entries[block.range()])
+    }
+
+    /// Decodes `block` on its own, like [`TimeSeriesDataPacker::decode`]
+    /// decodes the whole series. `None` past the last block.
+    pub fn decode_block(&self, block: usize) -> Option<Vec<TSSamples>> {
+        self.block_entries(block)
+            .map(|entries| decode_block(entries, &self.attributes).collect())
+    }
+
+    /// Decoded samples inside `[start, end]`; only the blocks returned by
+    /// [`Self::seek`] are read.
+    pub fn decode_range(&self, start: f64, end: f64) -> Vec<TSSamples> {
+        self.seek(start, end)
+            .flat_map(|block| {
+                decode_block(&self.entries[self.index[block].range()], &self.attributes)
+            })
+            .filter(|(ts, _)| *ts >= start && *ts <= end)
+            .collect()
+    }
+
+    /// Decoded samples of all blocks, in time order.
+    pub fn decode(&self) -> Vec<TSSamples> {
+        self.index
+            .iter()
+            .flat_map(|block| decode_block(&self.entries[block.range()], &self.attributes))
+            .collect()
+    }
+}
+
+/// Samples of one block; range chains round values like
+/// [`TimeSeriesDataPacker::unpack`].
+fn decode_block<'a>(
+    entries: &'a [TSPackedSamples],
+    attributes: &'a TSPackAttributes,
+) -> impl Iterator<Item = TSSamples> + 'a {
+    let round = !uses_bit_exact_encoding(&attributes.strategy_types);
+
+    decode_window_iter(
+        entries,
+        &attributes.strategy_types,
+        attributes.precision_epsilon,
+    )
+    .map(move |(ts, value)| {
+        if round {
+            (ts, round_to_precision(value, attributes.precision_epsilon))
+        } else {
+            (ts, value)
+        }
+    })
+}
+
+impl TimeSeriesDataPacker {
+    /// Copies the packed series into a [`TSPackBlockContainer`], decoding
+    /// every block once to build its index entry. Fails with
+    /// [`TSPackError::NotPacked`] before any `pack` call.
+    pub fn block_container(&self) -> Result<TSPackBlockContainer, TSPackError> {
+        let attributes = self.attributes.clone().ok_or(TSPackError::NotPacked)?;
+
+        let mut offsets: Vec<usize> = self
+            .window_starts
+            .iter()
+            .map(|(_, offset)| *offset)
+            .filter(|offset| *offset < self.packed_samples.len())
+            .collect();
+        // merged ranges leave several windows on the same offset
+        offsets.dedup();
+
+        let index = offsets
+            .iter()
+            .enumerate()
+            .map(|(block, &offset)| {
+                let to = offsets
+                    .get(block + 1)
+                    .copied()
+                    .unwrap_or(self.packed_samples.len());
+                let samples: Vec<TSSamples> =
+                    decode_block(&self.packed_samples[offset..to], &attributes).collect();
+
+                let (start, first) = samples.first().copied().unwrap_or((f64::NAN, f64::NAN));
+                let (end, last) = samples.last().copied().unwrap_or((f64::NAN, f64::NAN));
+
+                TSPackBlockIndexEntry {
+                    start,
+                    end,
+                    offset,
+                    entries: to - offset,
+                    count: samples.len(),
+                    // f64::min and f64::max ignore NaN operands
+                    min: samples.iter().map(|(_, v)| *v).fold(f64::NAN, f64::min),
+                    max: samples.iter().map(|(_, v)| *v).fold(f64::NAN, f64::max),
+                    sum: samples
+                        .iter()
+                        .map(|(_, v)| *v)
+                        .filter(|v| !v.is_nan())
+                        .sum(),
+                    first,
+                    last,
+                }
+            })
+            .collect();
+
+        Ok(TSPackBlockContainer {
+            attributes,
+            entries: self.packed_samples.clone(),
+            index,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::auto::default_auto_candidates;
+    use crate::test_support::pack;
+    use crate::TSPackStrategyType;
+
+    fn samples() -> Vec<TSSamples> {
+        (0..100)
+            .map(|i| (i as f64 * 0.1, ((i / 15) % 3) as f64 + (i % 4) as f64 * 0.5))
+            .collect()
+    }
+
+    #[test]
+    fn bit_exact_blocks_are_the_packing_windows() {
+        let strategies = vec![
+            TSPackStrategyType::TSPackXorStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+            TSPackStrategyType::TSPackDeltaOfDeltaStrategy,
+            TSPackStrategyType::TSPackSimple8bStrategy,
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates: default_auto_candidates(),
+                max_error: 0.0,
+            },
+        ];
+
+        for strategy in strategies {
+            let packer = pack(samples(), strategy.clone());
+            let container = packer.block_container().unwrap();
+            let decoded = packer.decode();
+
+            assert_eq!(container.len(), packer.window_starts.len(), "{strategy:?}");
+            assert_eq!(container.decode(), decoded, "{strategy:?}");
+            assert_eq!(
+                container.index().iter().map(|b| b.count).sum::<usize>(),
+                decoded.len()
+            );
+
+            let block = container.index()[3];
+            let samples = container.decode_block(3).unwrap();
+            assert_eq!(
+                (block.start, block.end),
+                (samples[0].0, samples[samples.len() - 1].0)
+            );
+            assert_eq!(
+                block.max,
+                samples.iter().map(|(_, v)| *v).fold(f64::MIN, f64::max),
+                "{strategy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn seeks_only_overlapping_blocks() {
+        let packer = pack(samples(), TSPackStrategyType::TSPackXorStrategy);
+        let container = packer.block_container().unwrap();
+
+        // windows start at 0.0, 1.1, 2.2, ...
+        assert_eq!(container.seek(2.5, 3.0), 2..3);
+        assert_eq!(container.seek(2.05, 3.35), 1..4);
+        assert_eq!(container.seek(3.0, 2.0), 0..0);
+        assert_eq!(container.seek(100.0, 200.0), 10..10);
+        assert_eq!(container.decode_block(10), None);
+
+        let expected: Vec<TSSamples> = packer
+            .decode()
+            .into_iter()
+            .filter(|(ts, _)| (2.05..=3.35).contains(ts))
+            .collect();
+        assert_eq!(container.decode_range(2.05, 3.35), expected);
+    }
+
+    #[test]
+    fn range_blocks_follow_merged_ranges() {
+        // one run across three windows, then a run per window
+        let samples = vec![
+            (0.0, 1.0),
+            (1.0, 1.0),
+            (2.5, 1.0),
+            (3.6, 2.0),
+            (4.0, 2.0),
+            (5.0, 3.0),
+        ];
+        let packer = pack(samples, TSPackStrategyType::TSPackRunLengthStrategy);
+        let container = packer.block_container().unwrap();
+
+        let bounds: Vec<(f64, f64, usize)> = container
+            .index()
+            .iter()
+            .map(|block| (block.start, block.end, block.entries))
+            .collect();
+        assert_eq!(bounds, vec![(0.0, 2.5, 1), (3.6, 4.0, 1), (5.0, 5.0, 1)]);
+        assert_eq!(container.decode(), packer.decode());
+
+        assert_eq!(
+            TimeSeriesDataPacker::new().block_container(),
+            Err(TSPackError::NotPacked)
+        );
+    }
+}
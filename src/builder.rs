@@ -0,0 +1,430 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::strategies::auto::default_auto_candidates;
+use crate::validation::validate_attributes;
+use crate::TSPackAttributes;
+use crate::TSPackError;
+use crate::TSPackPrecisionDataType;
+use crate::TSPackStrategyType;
+
+/// Window units accepted by the pipeline spec, largest first: `(suffix, microseconds)`.
+const WINDOW_UNITS: &[(&str, u64)] = &[
+    ("h", 3_600_000_000),
+    ("m", 60_000_000),
+    ("s", 1_000_000),
+    ("ms", 1_000),
+    ("us", 1),
+];
+
+/// Builder for [`TSPackAttributes`].
+///
+/// Defaults: 1 second window, [`TSPackPrecisionDataType::IoTSensors`] epsilon and
+/// the similar-values strategy when no strategy was added.
+#[derive(Debug, Clone)]
+pub struct TSPackAttributesBuilder {
+    strategy_types: Vec<TSPackStrategyType>,
+    microseconds_time_window: u64,
+    precision_epsilon: f64,
+}
+
+impl Default for TSPackAttributesBuilder {
+    fn default() -> Self {
+        Self {
+            strategy_types: Vec::new(),
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: TSPackPrecisionDataType::IoTSensors.epsilon(),
+        }
+    }
+}
+
+impl TSPackAttributesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a strategy to the chain.
+    pub fn strategy(mut self, strategy: TSPackStrategyType) -> Self {
+        self.strategy_types.push(strategy);
+        self
+    }
+
+    /// Replaces the whole strategy chain.
+    pub fn strategies(mut self, strategies: Vec<TSPackStrategyType>) -> Self {
+        self.strategy_types = strategies;
+        self
+    }
+
+    pub fn window(mut self, window: Duration) -> Self {
+        self.microseconds_time_window = window.as_micros().min(u64::MAX as u128) as u64;
+        self
+    }
+
+    pub fn window_microseconds(mut self, microseconds: u64) -> Self {
+        self.microseconds_time_window = microseconds;
+        self
+    }
+
+    pub fn precision_epsilon(mut self, epsilon: f64) -> Self {
+        self.precision_epsilon = epsilon;
+        self
+    }
+
+    pub fn precision(self, precision: TSPackPrecisionDataType) -> Self {
+        self.precision_epsilon(precision.epsilon())
+    }
+
+    /// Builds and validates the attributes.
+    pub fn build(self) -> Result<TSPackAttributes, TSPackError> {
+        let strategy_types = if self.strategy_types.is_empty() {
+            vec![TSPackStrategyType::TSPackSimilarValuesStrategy]
+        } else {
+            self.strategy_types
+        };
+
+        let attributes = TSPackAttributes {
+            strategy_types,
+            microseconds_time_window: self.microseconds_time_window,
+            precision_epsilon: self.precision_epsilon,
+        };
+
+        validate_attributes(&attributes)?;
+        Ok(attributes)
+    }
+}
+
+impl fmt::Display for TSPackStrategyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TSPackStrategyType::TSPackSimilarValuesStrategy => write!(f, "similar"),
+            TSPackStrategyType::TSPackMeanStrategy {
+                values_compression_percent,
+            } => write!(f, "mean({values_compression_percent})"),
+            TSPackStrategyType::TSPackXorStrategy => write!(f, "xor"),
+            TSPackStrategyType::TSPackDeltaStrategy => write!(f, "delta"),
+            TSPackStrategyType::TSPackDeltaOfDeltaStrategy => write!(f, "dod"),
+            TSPackStrategyType::TSPackRunLengthStrategy => write!(f, "rle"),
+            TSPackStrategyType::TSPackSimple8bStrategy => write!(f, "simple8b"),
+            TSPackStrategyType::TSPackCounterStrategy => write!(f, "counter"),
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates,
+                max_error,
+            } => {
+                write!(f, "auto({max_error}")?;
+                for candidate in candidates {
+                    write!(f, ",{candidate}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl FromStr for TSPackStrategyType {
+    type Err = TSPackError;
+
+    /// Parses `similar`, `mean(<percent>)`, `xor`, `delta`, `dod`, `rle`, `simple8b`,
+    /// `counter` or `auto(<max_error>[,<candidate>...])`. The long names used by the
+    /// showcase (`similar-values`, `delta-of-delta`, `run-length`, `xor-gorilla`) are accepted too.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+
+        if let Some(arguments) = name
+            .strip_prefix("auto(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let mut arguments = arguments.split(',');
+            let max_error = arguments
+                .next()
+                .and_then(|max_error| max_error.trim().parse::<f64>().ok())
+                .ok_or_else(|| TSPackError::InvalidSpec(format!("invalid auto bound `{s}`")))?;
+            let mut candidates = arguments
+                .map(str::parse)
+                .collect::<Result<Vec<TSPackStrategyType>, _>>()?;
+            if candidates.is_empty() {
+                candidates = default_auto_candidates();
+            }
+            return Ok(TSPackStrategyType::TSPackAutoStrategy {
+                candidates,
+                max_error,
+            });
+        }
+
+        if let Some(percent) = name
+            .strip_prefix("mean(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let values_compression_percent = percent
+                .trim()
+                .parse::<u8>()
+                .map_err(|_| TSPackError::InvalidSpec(format!("invalid mean percent `{s}`")))?;
+            return Ok(TSPackStrategyType::TSPackMeanStrategy {
+                values_compression_percent,
+            });
+        }
+
+        match name.as_str() {
+            "similar" | "similar-values" => Ok(TSPackStrategyType::TSPackSimilarValuesStrategy),
+            "xor" | "xor-gorilla" => Ok(TSPackStrategyType::TSPackXorStrategy),
+            "delta" => Ok(TSPackStrategyType::TSPackDeltaStrategy),
+            "dod" | "delta-of-delta" => Ok(TSPackStrategyType::TSPackDeltaOfDeltaStrategy),
+            "rle" | "run-length" => Ok(TSPackStrategyType::TSPackRunLengthStrategy),
+            "simple8b" => Ok(TSPackStrategyType::TSPackSimple8bStrategy),
+            "counter" => Ok(TSPackStrategyType::TSPackCounterStrategy),
+            _ => Err(TSPackError::InvalidSpec(format!("unknown strategy `{s}`"))),
+        }
+    }
+}
+
+fn parse_window(text: &str) -> Result<u64, TSPackError> {
+    let invalid = || TSPackError::InvalidSpec(format!("invalid window `{text}`"));
+    let text = text.trim();
+
+    let number_len = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(number_len);
+
+    let factor = if unit.is_empty() {
+        1
+    } else {
+        WINDOW_UNITS
+            .iter()
+            .find(|(suffix, _)| *suffix == unit)
+            .map(|(_, factor)| *factor)
+            .ok_or_else(invalid)?
+    };
+
+    // integer and fractional digits are scaled separately: 8.2 * 1e6 is not
+    // an integer in f64
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+
+    let parse_digits = |digits: &str| -> Result<u64, TSPackError> {
+        if digits.is_empty() {
+            Ok(0)
+        } else {
+            digits.parse::<u64>().map_err(|_| invalid())
+        }
+    };
+
+    let fraction_scale = u32::try_from(fraction.len())
+        .ok()
+        .and_then(|len| 10u64.checked_pow(len))
+        .ok_or_else(invalid)?;
+    let fraction_microseconds = parse_digits(fraction)?
+        .checked_mul(factor)
+        .ok_or_else(invalid)?;
+    if !fraction_microseconds.is_multiple_of(fraction_scale) {
+        return Err(invalid());
+    }
+
+    parse_digits(integer)?
+        .checked_mul(factor)
+        .and_then(|microseconds| microseconds.checked_add(fraction_microseconds / fraction_scale))
+        .ok_or_else(invalid)
+}
+
+fn format_window(microseconds: u64) -> String {
+    WINDOW_UNITS
+        .iter()
+        .find(|(_, factor)| microseconds != 0 && microseconds.is_multiple_of(*factor))
+        .map(|(suffix, factor)| format!("{}{suffix}", microseconds / factor))
+        .unwrap_or_else(|| format!("{microseconds}us"))
+}
+
+impl fmt::Display for TSPackAttributes {
+    /// Writes the compact pipeline spec, e.g. `window=1s;eps=0.001;similar|xor`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "window={};eps={};",
+            format_window(self.microseconds_time_window),
+            self.precision_epsilon
+        )?;
+
+        for (position, strategy) in self.strategy_types.iter().enumerate() {
+            if position > 0 {
+                write!(f, "|")?;
+            }
+            write!(f, "{strategy}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for TSPackAttributes {
+    type Err = TSPackError;
+
+    /// Parses a pipeline spec such as `window=1s;eps=1e-3;similar|xor`.
+    ///
+    /// Items are separated by `;`. `window` takes `h`, `m`, `s`, `ms` or `us`
+    /// (plain numbers are microseconds), `eps` a float; the item without `=` is
+    /// the `|`-separated strategy chain. Missing items use the builder defaults.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut builder = TSPackAttributesBuilder::new();
+        let mut chain_seen = false;
+
+        for item in s.split(';').map(str::trim).filter(|item| !item.is_empty()) {
+            match item.split_once('=') {
+                Some((key, value)) => match key.trim() {
+                    "window" => {
+                        builder = builder.window_microseconds(parse_window(value)?);
+                    }
+                    "eps" | "epsilon" => {
+                        let epsilon = value.trim().parse::<f64>().map_err(|_| {
+                            TSPackError::InvalidSpec(format!("invalid epsilon `{value}`"))
+                        })?;
+                        builder = builder.precision_epsilon(epsilon);
+                    }
+                    other => {
+                        return Err(TSPackError::InvalidSpec(format!("unknown key `{other}`")));
+                    }
+                },
+                None => {
+                    if chain_seen {
+                        return Err(TSPackError::InvalidSpec(
+                            "more than one strategy chain".to_string(),
+                        ));
+                    }
+                    chain_seen = true;
+
+                    let strategies = item
+                        .split('|')
+                        .map(str::parse)
+                        .collect::<Result<Vec<TSPackStrategyType>, _>>()?;
+                    builder = builder.strategies(strategies);
+                }
+            }
+        }
+
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_defaults() {
+        let attrs = TSPackAttributes::builder().build().unwrap();
+
+        assert_eq!(
+            attrs,
+            TSPackAttributes {
+                strategy_types: vec![TSPackStrategyType::TSPackSimilarValuesStrategy],
+                microseconds_time_window: 1_000_000,
+                precision_epsilon: TSPackPrecisionDataType::IoTSensors.epsilon(),
+            }
+        );
+    }
+
+    #[test]
+    fn builder_chain_and_validation() {
+        let attrs = TSPackAttributes::builder()
+            .strategy(TSPackStrategyType::TSPackMeanStrategy {
+                values_compression_percent: 5,
+            })
+            .strategy(TSPackStrategyType::TSPackRunLengthStrategy)
+            .window(Duration::from_millis(250))
+            .precision(TSPackPrecisionDataType::MilisValues)
+            .build()
+            .unwrap();
+
+        assert_eq!(attrs.strategy_types.len(), 2);
+        assert_eq!(attrs.microseconds_time_window, 250_000);
+        assert_eq!(attrs.precision_epsilon, 1e-3);
+
+        assert_eq!(
+            TSPackAttributes::builder().window_microseconds(0).build(),
+            Err(TSPackError::InvalidWindow)
+        );
+    }
+
+    #[test]
+    fn parse_spec() {
+        let attrs: TSPackAttributes = "window=1s;eps=1e-3;similar|xor".parse().unwrap();
+
+        assert_eq!(
+            attrs,
+            TSPackAttributes {
+                strategy_types: vec![
+                    TSPackStrategyType::TSPackSimilarValuesStrategy,
+                    TSPackStrategyType::TSPackXorStrategy,
+                ],
+                microseconds_time_window: 1_000_000,
+                precision_epsilon: 1e-3,
+            }
+        );
+
+        let attrs: TSPackAttributes = "mean(5) | run-length ; window=1.5ms".parse().unwrap();
+        assert_eq!(attrs.microseconds_time_window, 1_500);
+
+        assert_eq!(
+            attrs.strategy_types[0],
+            TSPackStrategyType::TSPackMeanStrategy {
+                values_compression_percent: 5,
+            }
+        );
+
+        let attrs: TSPackAttributes = "window=8.2s;xor".parse().unwrap();
+        assert_eq!(attrs.microseconds_time_window, 8_200_000);
+
+        let attrs: TSPackAttributes = "auto(0.1)".parse().unwrap();
+        assert_eq!(
+            attrs.strategy_types,
+            vec![TSPackStrategyType::TSPackAutoStrategy {
+                candidates: default_auto_candidates(),
+                max_error: 0.1,
+            }]
+        );
+    }
+
+    #[test]
+    fn spec_round_trips_through_text() {
+        for spec in [
+            "window=1s;eps=0.001;similar|xor",
+            "window=250ms;eps=0.00001;mean(5)|rle",
+            "window=1500us;eps=0;dod",
+            "window=2h;eps=0.000000001;simple8b",
+            "window=1s;eps=0.001;auto(0.05,rle,mean(5),xor)",
+        ] {
+            let attrs: TSPackAttributes = spec.parse().unwrap();
+            assert_eq!(attrs.to_string(), spec);
+            assert_eq!(attrs.to_string().parse::<TSPackAttributes>(), Ok(attrs));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_spec() {
+        assert!(matches!(
+            "window=1d;similar".parse::<TSPackAttributes>(),
+            Err(TSPackError::InvalidSpec(_))
+        ));
+        assert!(matches!(
+            "window=1.5us;similar".parse::<TSPackAttributes>(),
+            Err(TSPackError::InvalidSpec(_))
+        ));
+        assert!(matches!(
+            "window=.;similar".parse::<TSPackAttributes>(),
+            Err(TSPackError::InvalidSpec(_))
+        ));
+        assert!(matches!(
+            "gzip".parse::<TSPackAttributes>(),
+            Err(TSPackError::InvalidSpec(_))
+        ));
+        assert!(matches!(
+            "foo=1;similar".parse::<TSPackAttributes>(),
+            Err(TSPackError::InvalidSpec(_))
+        ));
+        assert_eq!(
+            "xor|similar".parse::<TSPackAttributes>(),
+            Err(TSPackError::InvalidStrategyChain { position: 1 })
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use std::ops::Range;
+
+use crate::TSPackError;
+use crate::TSPackedSamples;
+use crate::TSSamples;
+
+/// Read access to samples regardless of their layout, so strategies can pack
+/// tuple rows and separate timestamp / value columns alike.
+pub trait TSSampleSource {
+    fn len(&self) -> usize;
+
+    /// Sample at `index` as `(timestamp_seconds, value)`.
+    fn sample(&self, index: usize) -> TSSamples;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn timestamp(&self, index: usize) -> f64 {
+        self.sample(index).0
+    }
+
+    fn iter(&self) -> TSSampleIter<'_, Self> {
+        TSSampleIter {
+            source: self,
+            index: 0,
+            end: self.len(),
+        }
+    }
+
+    /// Index ranges of consecutive windows of `micro_window` microseconds,
+    /// for samples sorted by timestamp.
+    fn window_ranges(&self, micro_window: u64) -> Vec<Range<usize>> {
+        let window_len_seconds = (micro_window as f64) / 1_000_000.0;
+        let mut ranges = Vec::new();
+        let mut start = 0;
+
+        while start < self.len() {
+            let window_start_ts = self.timestamp(start);
+
+            // first index past the window, by binary search
+            let (mut low, mut high) = (start + 1, self.len());
+            while low < high {
+                let mid = low + (high - low) / 2;
+                if self.timestamp(mid) - window_start_ts <= window_len_seconds {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+
+            ranges.push(start..low);
+            start = low;
+        }
+
+        ranges
+    }
+}
+
+/// Iterator over the samples of a [`TSSampleSource`].
+pub struct TSSampleIter<'a, S: TSSampleSource + ?Sized> {
+    source: &'a S,
+    index: usize,
+    end: usize,
+}
+
+impl<S: TSSampleSource + ?Sized> Iterator for TSSampleIter<'_, S> {
+    type Item = TSSamples;
+
+    fn next(&mut self) -> Option<TSSamples> {
+        if self.index >= self.end {
+            return None;
+        }
+
+        let sample = self.source.sample(self.index);
+        self.index += 1;
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<S: TSSampleSource + ?Sized> ExactSizeIterator for TSSampleIter<'_, S> {}
+
+impl TSSampleSource for [TSSamples] {
+    fn len(&self) -> usize {
+        <[TSSamples]>::len(self)
+    }
+
+    fn sample(&self, index: usize) -> TSSamples {
+        self[index]
+    }
+}
+
+impl TSSampleSource for Vec<TSSamples> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn sample(&self, index: usize) -> TSSamples {
+        self[index]
+    }
+}
+
+impl<const N: usize> TSSampleSource for [TSSamples; N] {
+    fn len(&self) -> usize {
+        N
+    }
+
+    fn sample(&self, index: usize) -> TSSamples {
+        self[index]
+    }
+}
+
+/// Timestamp column: `f64` seconds or `i64` microseconds (the unit of
+/// `microseconds_time_window`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TSTimestampColumn<'a> {
+    Seconds(&'a [f64]),
+    Microseconds(&'a [i64]),
+}
+
+impl TSTimestampColumn<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            TSTimestampColumn::Seconds(timestamps) => timestamps.len(),
+            TSTimestampColumn::Microseconds(timestamps) => timestamps.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn seconds(&self, index: usize) -> f64 {
+        match self {
+            TSTimestampColumn::Seconds(timestamps) => timestamps[index],
+            TSTimestampColumn::Microseconds(timestamps) => timestamps[index] as f64 / 1_000_000.0,
+        }
+    }
+
+    fn is_sorted(&self) -> bool {
+        match self {
+            TSTimestampColumn::Seconds(timestamps) => timestamps.windows(2).all(|p| p[0] <= p[1]),
+            TSTimestampColumn::Microseconds(timestamps) => {
+                timestamps.windows(2).all(|p| p[0] <= p[1])
+            }
+        }
+    }
+}
+
+/// Slices accepted as the timestamp column of [`crate::TimeSeriesDataPacker::pack_columns`].
+pub trait TSPackTimestamps {
+    fn column(&self) -> TSTimestampColumn<'_>;
+}
+
+impl TSPackTimestamps for [f64] {
+    fn column(&self) -> TSTimestampColumn<'_> {
+        TSTimestampColumn::Seconds(self)
+    }
+}
+
+impl TSPackTimestamps for [i64] {
+    fn column(&self) -> TSTimestampColumn<'_> {
+        TSTimestampColumn::Microseconds(self)
+    }
+}
+
+/// Borrowed struct-of-arrays view of a series: one timestamp and one value
+/// column of equal length, e.g. straight from numpy, Arrow or a DAQ buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSColumns<'a> {
+    timestamps: TSTimestampColumn<'a>,
+    values: &'a [f64],
+}
+
+impl<'a> TSColumns<'a> {
+    pub fn new<T: TSPackTimestamps + ?Sized>(
+        timestamps: &'a T,
+        values: &'a [f64],
+    ) -> Result<Self, TSPackError> {
+        let timestamps = timestamps.column();
+
+        if timestamps.len() != values.len() {
+            return Err(TSPackError::ColumnLengthMismatch {
+                timestamps: timestamps.len(),
+                values: values.len(),
+            });
+        }
+
+        Ok(Self { timestamps, values })
+    }
+
+    pub fn timestamps(&self) -> TSTimestampColumn<'a> {
+        self.timestamps
+    }
+
+    pub fn values(&self) -> &'a [f64] {
+        self.values
+    }
+
+    /// Sub-view of the samples in `range`, without copying.
+    pub fn slice(&self, range: Range<usize>) -> TSColumns<'a> {
+        let timestamps = match self.timestamps {
+            TSTimestampColumn::Seconds(timestamps) => {
+                TSTimestampColumn::Seconds(&timestamps[range.clone()])
+            }
+            TSTimestampColumn::Microseconds(timestamps) => {
+                TSTimestampColumn::Microseconds(&timestamps[range.clone()])
+            }
+        };
+
+        TSColumns {
+            timestamps,
+            values: &self.values[range],
+        }
+    }
+
+    pub fn is_sorted(&self) -> bool {
+        self.timestamps.is_sorted()
+    }
+}
+
+impl TSSampleSource for TSColumns<'_> {
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    fn sample(&self, index: usize) -> TSSamples {
+        (self.timestamps.seconds(index), self.values[index])
+    }
+
+    fn timestamp(&self, index: usize) -> f64 {
+        self.timestamps.seconds(index)
+    }
+}
+
+/// Packed series as three parallel arrays: range starts, range ends and values.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TSPackedColumns {
+    pub starts: Vec<f64>,
+    pub ends: Vec<f64>,
+    pub values: Vec<f64>,
+}
+
+impl TSPackedColumns {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Entries as `((start, end), value)` tuples.
+    pub fn iter(&self) -> impl Iterator<Item = TSPackedSamples> + '_ {
+        self.starts
+            .iter()
+            .zip(&self.ends)
+            .zip(&self.values)
+            .map(|((start, end), value)| ((*start, *end), *value))
+    }
+}
+
+impl From<&[TSPackedSamples]> for TSPackedColumns {
+    fn from(packed: &[TSPackedSamples]) -> Self {
+        let mut columns = TSPackedColumns {
+            starts: Vec::with_capacity(packed.len()),
+            ends: Vec::with_capacity(packed.len()),
+            values: Vec::with_capacity(packed.len()),
+        };
+
+        for &((start, end), value) in packed {
+            columns.starts.push(start);
+            columns.ends.push(end);
+            columns.values.push(value);
+        }
+
+        columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_read_like_rows() {
+        let micros: [i64; 3] = [0, 500_000, 2_000_000];
+        let seconds = [0.0, 0.5, 2.0];
+        let values = [1.0, 2.0, 3.0];
+
+        let from_micros = TSColumns::new(&micros[..], &values).unwrap();
+        let from_seconds = TSColumns::new(&seconds[..], &values).unwrap();
+        let rows = vec![(0.0, 1.0), (0.5, 2.0), (2.0, 3.0)];
+
+        assert_eq!(from_micros.iter().collect::<Vec<_>>(), rows);
+        assert_eq!(from_seconds.iter().collect::<Vec<_>>(), rows);
+        assert_eq!(from_seconds.slice(1..3).sample(0), (0.5, 2.0));
+
+        assert_eq!(
+            TSColumns::new(&seconds[..], &values[..2]),
+            Err(TSPackError::ColumnLengthMismatch {
+                timestamps: 3,
+                values: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn window_ranges_match_row_windows() {
+        let rows = vec![
+            (0.00, 1.0),
+            (0.05, 1.0),
+            (0.10, 2.0),
+            (0.15, 2.0),
+            (0.21, 3.0),
+        ];
+
+        assert_eq!(rows.window_ranges(100_000), vec![0..3, 3..5]);
+        assert_eq!(rows[..0].window_ranges(100_000), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn packed_columns_round_trip() {
+        let packed = vec![((0.0, 1.0), 5.0), ((2.0, 2.0), 6.0)];
+        let columns = TSPackedColumns::from(packed.as_slice());
+
+        assert_eq!(columns.starts, vec![0.0, 2.0]);
+        assert_eq!(columns.ends, vec![1.0, 2.0]);
+        assert_eq!(columns.values, vec![5.0, 6.0]);
+        assert_eq!(columns.iter().collect::<Vec<_>>(), packed);
+    }
+}
@@ -0,0 +1,354 @@
+use crate::helpers::round_to_precision;
+use crate::helpers::uses_bit_exact_encoding;
+use crate::strategies::auto::TSPackAutoStrategy;
+use crate::strategies::auto::TSPackAutoUnpackIter;
+use crate::strategies::counter::TSPackCounterStrategy;
+use crate::strategies::counter::TSPackCounterUnpackIter;
+use crate::strategies::delta::TSPackDeltaStrategy;
+use crate::strategies::delta::TSPackDeltaUnpackIter;
+use crate::strategies::delta_of_delta::TSPackDeltaOfDeltaStrategy;
+use crate::strategies::delta_of_delta::TSPackDeltaOfDeltaUnpackIter;
+use crate::strategies::run_length::TSPackRunLengthStrategy;
+use crate::strategies::run_length::TSPackRunLengthUnpackIter;
+use crate::strategies::simple_8b::TSPackSimple8bStrategy;
+use crate::strategies::simple_8b::TSPackSimple8bUnpackIter;
+use crate::strategies::xor_gorilla::TSPackXorGorillaStrategy;
+use crate::strategies::xor_gorilla::TSPackXorGorillaUnpackIter;
+use crate::TSPackStrategyType;
+use crate::TSPackedSamples;
+use crate::TSSamples;
+use crate::TimeSeriesDataPacker;
+
+/// Lazy decoder of the packed entries of one window, see [`decode_window_iter`].
+#[derive(Debug, Clone)]
+pub enum TSPackDecodeIter<'a> {
+    Xor(TSPackXorGorillaUnpackIter<'a>),
+    Delta(TSPackDeltaUnpackIter<'a>),
+    DeltaOfDelta(TSPackDeltaOfDeltaUnpackIter<'a>),
+    Simple8b(TSPackSimple8bUnpackIter<'a>),
+    Counter(TSPackCounterUnpackIter<'a>),
+    Auto(Box<TSPackAutoUnpackIter<'a>>),
+    /// Range strategies: start and end point of every range.
+    Ranges(TSPackRunLengthUnpackIter<'a>),
+}
+
+impl Iterator for TSPackDecodeIter<'_> {
+    type Item = TSSamples;
+
+    fn next(&mut self) -> Option<TSSamples> {
+        match self {
+            TSPackDecodeIter::Xor(samples) => samples.next(),
+            TSPackDecodeIter::Delta(samples) => samples.next(),
+            TSPackDecodeIter::DeltaOfDelta(samples) => samples.next(),
+            TSPackDecodeIter::Simple8b(samples) => samples.next(),
+            TSPackDecodeIter::Counter(samples) => samples.next(),
+            TSPackDecodeIter::Auto(samples) => samples.next(),
+            TSPackDecodeIter::Ranges(samples) => samples.next(),
+        }
+    }
+}
+
+/// Lazy [`crate::helpers::decode_window`]: picks the decoder of the last
+/// strategy in the chain without decoding anything yet.
+pub fn decode_window_iter<'a>(
+    packed: &'a [TSPackedSamples],
+    strategies: &[TSPackStrategyType],
+    precision_epsilon: f64,
+) -> TSPackDecodeIter<'a> {
+    match strategies.last() {
+        Some(TSPackStrategyType::TSPackXorStrategy) => {
+            TSPackDecodeIter::Xor(TSPackXorGorillaStrategy::iter_unpack(packed))
+        }
+        Some(TSPackStrategyType::TSPackDeltaStrategy) => {
+            TSPackDecodeIter::Delta(TSPackDeltaStrategy::iter_unpack(packed))
+        }
+        Some(TSPackStrategyType::TSPackDeltaOfDeltaStrategy) => {
+            TSPackDecodeIter::DeltaOfDelta(TSPackDeltaOfDeltaStrategy::iter_unpack(packed))
+        }
+        Some(TSPackStrategyType::TSPackSimple8bStrategy) => TSPackDecodeIter::Simple8b(
+            TSPackSimple8bStrategy::iter_unpack(packed, precision_epsilon),
+        ),
+        Some(TSPackStrategyType::TSPackCounterStrategy) => {
+            TSPackDecodeIter::Counter(TSPackCounterStrategy::iter_unpack(packed))
+        }
+        Some(TSPackStrategyType::TSPackAutoStrategy { .. }) => TSPackDecodeIter::Auto(Box::new(
+            TSPackAutoStrategy::iter_unpack(packed, precision_epsilon),
+        )),
+        _ => TSPackDecodeIter::Ranges(TSPackRunLengthStrategy::iter_unpack(packed)),
+    }
+}
+
+/// Iterator returned by [`TimeSeriesDataPacker::iter_unpack`] and
+/// [`TimeSeriesDataPacker::iter_decode`]. Holds one window decoder at a time,
+/// so memory stays constant whatever the length of the series.
+#[derive(Debug, Clone)]
+pub struct TSPackSeriesIter<'a> {
+    packed: &'a [TSPackedSamples],
+    // windows still to decode, for chains decoded window by window
+    window_starts: &'a [(f64, usize)],
+    strategies: &'a [TSPackStrategyType],
+    precision_epsilon: f64,
+    // values are rounded to this epsilon, like `unpack` does
+    round_epsilon: Option<f64>,
+    current: TSPackDecodeIter<'a>,
+}
+
+impl Iterator for TSPackSeriesIter<'_> {
+    type Item = TSSamples;
+
+    fn next(&mut self) -> Option<TSSamples> {
+        loop {
+            if let Some((ts, value)) = self.current.next() {
+                return Some(match self.round_epsilon {
+                    Some(eps) => (ts, round_to_precision(value, eps)),
+                    None => (ts, value),
+                });
+            }
+
+            let (&(_, from), rest) = self.window_starts.split_first()?;
+            let to = rest
+                .first()
+                .map(|(_, offset)| *offset)
+                .unwrap_or(self.packed.len());
+
+            self.current = decode_window_iter(
+                &self.packed[from..to],
+                self.strategies,
+                self.precision_epsilon,
+            );
+            self.window_starts = rest;
+        }
+    }
+}
+
+impl TimeSeriesDataPacker {
+    /// Lazy [`Self::unpack`]: yields the same samples one at a time, without
+    /// allocating the expanded series.
+    pub fn iter_unpack(&self) -> TSPackSeriesIter<'_> {
+        let (strategies, precision_epsilon) = match &self.attributes {
+            Some(attributes) => (
+                attributes.strategy_types.as_slice(),
+                attributes.precision_epsilon,
+            ),
+            None => (&[][..], 0.0),
+        };
+
+        let auto = matches!(
+            strategies.last(),
+            Some(TSPackStrategyType::TSPackAutoStrategy { .. })
+        );
+
+        let current = if auto {
+            decode_window_iter(&self.packed_samples, strategies, precision_epsilon)
+        } else {
+            TSPackDecodeIter::Ranges(TSPackRunLengthStrategy::iter_unpack(&self.packed_samples))
+        };
+
+        TSPackSeriesIter {
+            packed: &self.packed_samples,
+            window_starts: &[],
+            strategies,
+            precision_epsilon,
+            round_epsilon: (!auto).then_some(precision_epsilon),
+            current,
+        }
+    }
+
+    /// Entries from the last one starting at or before `start` on: packed
+    /// ranges (values rounded like [`Self::unpack`]) for range chains, decoded
+    /// samples as zero-length ranges for bit-exact chains. Windows are decoded
+    /// only as the iterator reaches them.
+    pub(crate) fn entries_from(
+        &self,
+        start: f64,
+    ) -> Box<dyn Iterator<Item = TSPackedSamples> + '_> {
+        let Some(attributes) = &self.attributes else {
+            return Box::new(std::iter::empty());
+        };
+
+        if !uses_bit_exact_encoding(&attributes.strategy_types) {
+            let first = self
+                .packed_samples
+                .partition_point(|((range_start, _), _)| *range_start <= start)
+                .saturating_sub(1);
+
+            return Box::new(
+                self.packed_samples[first..]
+                    .iter()
+                    .map(move |&(range, value)| {
+                        (
+                            range,
+                            round_to_precision(value, attributes.precision_epsilon),
+                        )
+                    }),
+            );
+        }
+
+        let windows = &self.window_starts;
+        let first = windows
+            .partition_point(|(window_start, _)| *window_start <= start)
+            .saturating_sub(1);
+
+        Box::new((first..windows.len()).flat_map(move |window| {
+            let from = windows[window].1;
+            let to = windows
+                .get(window + 1)
+                .map(|(_, offset)| *offset)
+                .unwrap_or(self.packed_samples.len());
+
+            decode_window_iter(
+                &self.packed_samples[from..to],
+                &attributes.strategy_types,
+                attributes.precision_epsilon,
+            )
+            .map(|(ts, value)| ((ts, ts), value))
+        }))
+    }
+
+    /// [`Self::entries_from`] starting with the entry whose value holds at
+    /// `start`: only the last entry starting at or before `start` is kept, and
+    /// not even that one when it is the last of the series and ends before
+    /// `start`.
+    pub(crate) fn entries_held_from(
+        &self,
+        start: f64,
+    ) -> impl Iterator<Item = TSPackedSamples> + '_ {
+        let mut entries = self.entries_from(start).peekable();
+
+        let mut held = None;
+        while let Some(entry) = entries.next_if(|((range_start, _), _)| *range_start <= start) {
+            held = Some(entry);
+        }
+        if let Some(((_, range_end), _)) = held {
+            // the last entry of the series holds only until its own end
+            if range_end < start && entries.peek().is_none() {
+                held = None;
+            }
+        }
+
+        held.into_iter().chain(entries)
+    }
+
+    /// Lazy [`Self::decode`]: decodes window by window as the iterator
+    /// advances, so stopping early (e.g. with `take_while` on a time bound)
+    /// skips the remaining windows entirely.
+    pub fn iter_decode(&self) -> TSPackSeriesIter<'_> {
+        let Some(attributes) = &self.attributes else {
+            return self.iter_unpack();
+        };
+
+        if !uses_bit_exact_encoding(&attributes.strategy_types) {
+            return self.iter_unpack();
+        }
+
+        TSPackSeriesIter {
+            packed: &self.packed_samples,
+            window_starts: &self.window_starts,
+            strategies: &attributes.strategy_types,
+            precision_epsilon: attributes.precision_epsilon,
+            round_epsilon: None,
+            current: TSPackDecodeIter::Ranges(TSPackRunLengthStrategy::iter_unpack(&[])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::auto::default_auto_candidates;
+    use crate::TSPackAttributes;
+
+    fn all_strategies() -> Vec<TSPackStrategyType> {
+        vec![
+            TSPackStrategyType::TSPackSimilarValuesStrategy,
+            TSPackStrategyType::TSPackMeanStrategy {
+                values_compression_percent: 10,
+            },
+            TSPackStrategyType::TSPackXorStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+            TSPackStrategyType::TSPackDeltaOfDeltaStrategy,
+            TSPackStrategyType::TSPackRunLengthStrategy,
+            TSPackStrategyType::TSPackSimple8bStrategy,
+            TSPackStrategyType::TSPackCounterStrategy,
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates: default_auto_candidates(),
+                max_error: 0.0,
+            },
+        ]
+    }
+
+    // unpack rounds XOR words like plain values, which can produce NaN
+    fn bits(samples: impl IntoIterator<Item = TSSamples>) -> Vec<(u64, u64)> {
+        samples
+            .into_iter()
+            .map(|(ts, value)| (ts.to_bits(), value.to_bits()))
+            .collect()
+    }
+
+    fn samples() -> Vec<TSSamples> {
+        (0..300)
+            .map(|i| {
+                let ts = i as f64 * 0.05;
+                (ts, ((i / 7) % 5) as f64 * 1.5 + (i % 3) as f64 * 0.001)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn iterators_match_collected_output() {
+        for strategy in all_strategies() {
+            let mut packer = TimeSeriesDataPacker::new();
+            packer
+                .pack(
+                    samples(),
+                    TSPackAttributes {
+                        strategy_types: vec![strategy.clone()],
+                        microseconds_time_window: 1_000_000,
+                        precision_epsilon: 0.001,
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                bits(packer.iter_unpack()),
+                bits(packer.unpack().1),
+                "{strategy:?}"
+            );
+            assert_eq!(
+                bits(packer.iter_decode()),
+                bits(packer.decode()),
+                "{strategy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn stops_at_time_bound_without_decoding_the_rest() {
+        let mut packer = TimeSeriesDataPacker::new();
+        packer
+            .pack(
+                samples(),
+                TSPackAttributes {
+                    strategy_types: vec![TSPackStrategyType::TSPackXorStrategy],
+                    microseconds_time_window: 1_000_000,
+                    precision_epsilon: 0.001,
+                },
+            )
+            .unwrap();
+
+        let mut decoded = packer.iter_decode();
+        let head: Vec<TSSamples> = decoded.by_ref().take_while(|(ts, _)| *ts < 2.0).collect();
+
+        assert_eq!(head, samples()[..40].to_vec());
+        // only the windows up to the bound were started
+        assert_eq!(decoded.window_starts.len(), packer.window_starts.len() - 2);
+    }
+
+    #[test]
+    fn empty_packer_yields_nothing() {
+        let packer = TimeSeriesDataPacker::new();
+
+        assert_eq!(packer.iter_unpack().next(), None);
+        assert_eq!(packer.iter_decode().next(), None);
+    }
+}
@@ -0,0 +1,412 @@
+use crate::helpers::TIMESTAMP_TOLERANCE;
+use crate::lookup::interpolate;
+use crate::lookup::TSPackInterpolation;
+use crate::TSPackError;
+use crate::TSPackedSamples;
+use crate::TSSamples;
+use crate::TimeSeriesDataPacker;
+
+/// How [`TimeSeriesDataPacker::unpack_to_grid`] fills grid points between two
+/// entries. Inside a packed range every mode returns the range's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TSPackGridInterpolation {
+    /// Value of the last entry at or before the grid point.
+    #[default]
+    Step,
+    /// Straight line between the surrounding entries.
+    Linear,
+    /// Natural cubic spline through the entries between two gaps.
+    CubicSpline,
+}
+
+/// What a grid point that falls into a gap becomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TSPackGapPolicy {
+    /// Emitted with a NaN value.
+    #[default]
+    Nan,
+    /// Left out of the grid.
+    Skip,
+    /// Emitted with the last valid value before it (NaN when there is none).
+    Hold,
+}
+
+/// Interpolation and gap handling of [`TimeSeriesDataPacker::unpack_to_grid`].
+///
+/// Grid points before the first or after the last entry, next to a NaN sample
+/// (a gap marker) or between entries further apart than `max_gap` seconds are
+/// gaps. A single packed range is never split by `max_gap`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TSPackGridMode {
+    pub interpolation: TSPackGridInterpolation,
+    pub max_gap: Option<f64>,
+    pub gap_policy: TSPackGapPolicy,
+}
+
+impl TSPackGridMode {
+    pub fn new(interpolation: TSPackGridInterpolation) -> Self {
+        Self {
+            interpolation,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_gap(mut self, seconds: f64) -> Self {
+        self.max_gap = Some(seconds);
+        self
+    }
+
+    pub fn with_gap_policy(mut self, policy: TSPackGapPolicy) -> Self {
+        self.gap_policy = policy;
+        self
+    }
+}
+
+impl From<TSPackGridInterpolation> for TSPackGridMode {
+    fn from(interpolation: TSPackGridInterpolation) -> Self {
+        Self::new(interpolation)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Knot {
+    ts: f64,
+    value: f64,
+    // start point of a range whose end point is the next knot
+    flat: bool,
+    gap_after: bool,
+    second_derivative: f64,
+    last_valid: Option<f64>,
+}
+
+fn collect_knots(
+    entries: impl Iterator<Item = TSPackedSamples>,
+    end: f64,
+    mode: &TSPackGridMode,
+) -> Vec<Knot> {
+    let mut knots: Vec<Knot> = Vec::new();
+
+    let push = |knots: &mut Vec<Knot>, ts: f64, value: f64| match knots.last_mut() {
+        // duplicate timestamps: the later sample wins, like step-hold
+        Some(last) if ts <= last.ts + TIMESTAMP_TOLERANCE => last.value = value,
+        _ => knots.push(Knot {
+            ts,
+            value,
+            flat: false,
+            gap_after: false,
+            second_derivative: 0.0,
+            last_valid: None,
+        }),
+    };
+
+    for ((start, range_end), value) in entries {
+        push(&mut knots, start, value);
+        if range_end != start {
+            if let Some(last) = knots.last_mut() {
+                last.flat = true;
+            }
+            push(&mut knots, range_end, value);
+        }
+
+        // one entry past the grid is kept to interpolate up to `end`
+        if start > end {
+            break;
+        }
+    }
+
+    let mut last_valid = None;
+    for index in 0..knots.len() {
+        if !knots[index].value.is_nan() {
+            last_valid = Some(knots[index].value);
+        }
+        knots[index].last_valid = last_valid;
+
+        if let Some(next) = knots.get(index + 1).copied() {
+            let knot = &mut knots[index];
+            let too_far = mode
+                .max_gap
+                .is_some_and(|max_gap| next.ts - knot.ts > max_gap);
+            knot.gap_after = knot.value.is_nan() || next.value.is_nan() || (!knot.flat && too_far);
+        }
+    }
+
+    if mode.interpolation == TSPackGridInterpolation::CubicSpline {
+        let mut from = 0;
+        for index in 0..knots.len() {
+            if knots[index].gap_after || index + 1 == knots.len() {
+                natural_spline(&mut knots[from..=index]);
+                from = index + 1;
+            }
+        }
+    }
+
+    knots
+}
+
+/// Second derivatives of the natural cubic spline through `knots` (zero at
+/// both ends), by the tridiagonal (Thomas) algorithm.
+fn natural_spline(knots: &mut [Knot]) {
+    if knots.len() < 3 {
+        return;
+    }
+
+    let interior = knots.len() - 2;
+    let mut upper = vec![0.0; interior];
+    let mut rhs = vec![0.0; interior];
+
+    for row in 0..interior {
+        let (previous, knot, next) = (knots[row], knots[row + 1], knots[row + 2]);
+        let h0 = knot.ts - previous.ts;
+        let h1 = next.ts - knot.ts;
+        let slope_change =
+            6.0 * ((next.value - knot.value) / h1 - (knot.value - previous.value) / h0);
+
+        let (upper_previous, rhs_previous) = match row {
+            0 => (0.0, 0.0),
+            _ => (upper[row - 1], rhs[row - 1]),
+        };
+        let diagonal = 2.0 * (h0 + h1) - h0 * upper_previous;
+
+        upper[row] = h1 / diagonal;
+        rhs[row] = (slope_change - h0 * rhs_previous) / diagonal;
+    }
+
+    let mut following = 0.0;
+    for row in (0..interior).rev() {
+        following = rhs[row] - upper[row] * following;
+        knots[row + 1].second_derivative = following;
+    }
+}
+
+/// Value at `ts` from the knots around it: step and linear interpolation are
+/// those of [`TimeSeriesDataPacker::value_at`], the spline uses the knots'
+/// second derivatives.
+fn grid_value(knots: &[Knot], ts: f64, interpolation: TSPackGridInterpolation) -> Option<f64> {
+    let index = knots
+        .partition_point(|knot| knot.ts <= ts + TIMESTAMP_TOLERANCE)
+        .checked_sub(1)?;
+    let knot = knots[index];
+
+    if (ts - knot.ts).abs() <= TIMESTAMP_TOLERANCE {
+        return (!knot.value.is_nan()).then_some(knot.value);
+    }
+
+    let next = *knots.get(index + 1)?;
+    if knot.gap_after {
+        return None;
+    }
+
+    let mode = match interpolation {
+        _ if knot.flat => TSPackInterpolation::Previous,
+        TSPackGridInterpolation::Step => TSPackInterpolation::Previous,
+        TSPackGridInterpolation::Linear => TSPackInterpolation::Linear,
+        TSPackGridInterpolation::CubicSpline => {
+            let h = next.ts - knot.ts;
+            let a = (next.ts - ts) / h;
+            let b = (ts - knot.ts) / h;
+            let value = a * knot.value
+                + b * next.value
+                + ((a * a * a - a) * knot.second_derivative
+                    + (b * b * b - b) * next.second_derivative)
+                    * h
+                    * h
+                    / 6.0;
+            return (!value.is_nan()).then_some(value);
+        }
+    };
+
+    interpolate(
+        Some(((knot.ts, knot.ts), knot.value)),
+        Some(((next.ts, next.ts), next.value)),
+        ts,
+        mode,
+    )
+}
+
+impl TimeSeriesDataPacker {
+    /// Resamples the packed series onto the regular grid `start`, `start +
+    /// step`, ... up to `end` (seconds, inclusive), see [`TSPackGridMode`].
+    ///
+    /// Only the entries around `[start, end]` are read; bit-exact chains decode
+    /// just the windows the grid overlaps.
+    pub fn unpack_to_grid(
+        &self,
+        start: f64,
+        step_microseconds: u64,
+        end: f64,
+        mode: TSPackGridMode,
+    ) -> Result<Vec<TSSamples>, TSPackError> {
+        if step_microseconds == 0 {
+            return Err(TSPackError::InvalidWindow);
+        }
+
+        if start.is_nan() || end.is_nan() || start > end {
+            return Ok(Vec::new());
+        }
+
+        let invalid_range = TSPackError::InvalidTimeRange { start, end };
+
+        if !start.is_finite() || !end.is_finite() {
+            return Err(invalid_range);
+        }
+
+        let step = step_microseconds as f64 / 1_000_000.0;
+        let steps = ((end - start) / step + 1e-9).floor();
+
+        if steps >= usize::MAX as f64 {
+            return Err(invalid_range);
+        }
+
+        let points = steps as usize + 1;
+        let mut grid = Vec::new();
+        grid.try_reserve(points).map_err(|_| invalid_range)?;
+
+        let knots = collect_knots(self.entries_from(start), end, &mode);
+
+        for point in 0..points {
+            let ts = start + point as f64 * step;
+
+            match grid_value(&knots, ts, mode.interpolation) {
+                Some(value) => grid.push((ts, value)),
+                None => match mode.gap_policy {
+                    TSPackGapPolicy::Nan => grid.push((ts, f64::NAN)),
+                    TSPackGapPolicy::Skip => {}
+                    TSPackGapPolicy::Hold => {
+                        let held = knots
+                            .partition_point(|knot| knot.ts <= ts + TIMESTAMP_TOLERANCE)
+                            .checked_sub(1)
+                            .and_then(|index| knots[index].last_valid);
+                        grid.push((ts, held.unwrap_or(f64::NAN)));
+                    }
+                },
+            }
+        }
+
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::pack;
+    use crate::TSPackStrategyType;
+
+    fn values(grid: &[TSSamples]) -> Vec<f64> {
+        grid.iter().map(|(_, value)| *value).collect()
+    }
+
+    #[test]
+    fn step_and_linear_on_ranges() {
+        // ((0, 1), 1.0), ((2, 3), 3.0)
+        let samples = vec![(0.0, 1.0), (1.0, 1.0), (2.0, 3.0), (3.0, 3.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackRunLengthStrategy);
+
+        let step = packer
+            .unpack_to_grid(0.0, 500_000, 3.0, TSPackGridInterpolation::Step.into())
+            .unwrap();
+        assert_eq!(values(&step), vec![1.0, 1.0, 1.0, 1.0, 3.0, 3.0, 3.0]);
+        assert_eq!(step[1].0, 0.5);
+
+        let linear = packer
+            .unpack_to_grid(0.0, 500_000, 3.0, TSPackGridInterpolation::Linear.into())
+            .unwrap();
+        assert_eq!(values(&linear), vec![1.0, 1.0, 1.0, 2.0, 3.0, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn cubic_spline_follows_smooth_signals() {
+        let samples: Vec<TSSamples> = (0..=40)
+            .map(|i| (i as f64 * 0.25, (i as f64 * 0.25).sin()))
+            .collect();
+        let packer = pack(samples, TSPackStrategyType::TSPackXorStrategy);
+
+        let mode = TSPackGridMode::new(TSPackGridInterpolation::CubicSpline);
+        let grid = packer.unpack_to_grid(1.0, 100_000, 9.0, mode).unwrap();
+        assert_eq!(grid.len(), 81);
+
+        let worst = grid
+            .iter()
+            .map(|(ts, value)| (value - ts.sin()).abs())
+            .fold(0.0, f64::max);
+        assert!(worst < 1e-3, "{worst}");
+
+        let linear = packer
+            .unpack_to_grid(1.0, 100_000, 9.0, TSPackGridInterpolation::Linear.into())
+            .unwrap();
+        let linear_worst = linear
+            .iter()
+            .map(|(ts, value)| (value - ts.sin()).abs())
+            .fold(0.0, f64::max);
+        assert!(worst < linear_worst / 10.0);
+    }
+
+    #[test]
+    fn gap_policies() {
+        let mut samples: Vec<TSSamples> = (0..=4).map(|i| (i as f64, i as f64)).collect();
+        samples.extend((10..=12).map(|i| (i as f64, 10.0)));
+        let packer = pack(samples, TSPackStrategyType::TSPackXorStrategy);
+
+        let linear = TSPackGridMode::new(TSPackGridInterpolation::Linear).with_max_gap(2.0);
+        let grid =
+            |mode: TSPackGridMode| packer.unpack_to_grid(3.0, 2_000_000, 15.0, mode).unwrap();
+
+        // grid 3, 5, 7, 9, 11, 13, 15; data ends at 12
+        let nan = grid(linear);
+        assert_eq!(nan.len(), 7);
+        assert_eq!(nan[0], (3.0, 3.0));
+        assert!(nan[1..4].iter().all(|(_, value)| value.is_nan()));
+        assert_eq!(nan[4], (11.0, 10.0));
+        assert!(nan[5].1.is_nan());
+
+        let skipped = grid(linear.with_gap_policy(TSPackGapPolicy::Skip));
+        assert_eq!(skipped, vec![(3.0, 3.0), (11.0, 10.0)]);
+
+        let held = grid(linear.with_gap_policy(TSPackGapPolicy::Hold));
+        assert_eq!(values(&held), vec![3.0, 4.0, 4.0, 4.0, 10.0, 10.0, 10.0]);
+
+        // without max_gap the line bridges 4..10
+        let bridged = grid(TSPackGridInterpolation::Linear.into());
+        assert_eq!(bridged[1], (5.0, 5.0));
+    }
+
+    #[test]
+    fn nan_samples_are_gaps() {
+        let samples = vec![(0.0, 1.0), (1.0, f64::NAN), (2.0, 3.0), (3.0, 5.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackXorStrategy);
+
+        let mode = TSPackGridMode::new(TSPackGridInterpolation::CubicSpline)
+            .with_gap_policy(TSPackGapPolicy::Skip);
+        let grid = packer.unpack_to_grid(0.0, 500_000, 3.0, mode).unwrap();
+
+        assert_eq!(grid, vec![(0.0, 1.0), (2.0, 3.0), (2.5, 4.0), (3.0, 5.0)]);
+    }
+
+    #[test]
+    fn invalid_grids() {
+        let packer = pack(vec![(0.0, 1.0)], TSPackStrategyType::TSPackXorStrategy);
+
+        assert_eq!(
+            packer.unpack_to_grid(0.0, 0, 1.0, TSPackGridMode::default()),
+            Err(TSPackError::InvalidWindow)
+        );
+        assert_eq!(
+            packer.unpack_to_grid(2.0, 1_000, 1.0, TSPackGridMode::default()),
+            Ok(vec![])
+        );
+        assert_eq!(
+            packer.unpack_to_grid(0.0, 1_000, f64::INFINITY, TSPackGridMode::default()),
+            Err(TSPackError::InvalidTimeRange {
+                start: 0.0,
+                end: f64::INFINITY
+            })
+        );
+        assert_eq!(
+            packer.unpack_to_grid(0.0, 1, 1e12, TSPackGridMode::default()),
+            Err(TSPackError::InvalidTimeRange {
+                start: 0.0,
+                end: 1e12
+            })
+        );
+    }
+}
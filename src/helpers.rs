@@ -1,5 +1,13 @@
-use std::cmp::Ordering;
+use std::borrow::Cow;
+use std::mem::size_of;
+use std::ops::Range;
+use std::time::Duration;
+use std::time::Instant;
 
+use crate::columns::TSColumns;
+use crate::columns::TSSampleSource;
+use crate::decode::decode_window_iter;
+use crate::TSPackAttributes;
 use crate::TSPackStrategyType;
 use crate::TSPackedSamples;
 use crate::TSSamples;
@@ -9,135 +17,258 @@ use crate::strategies::similar_values::similar_values_pack;
 use crate::strategies::mean_based_compression::mean_pack;
 use crate::strategies::mean_based_compression::mean_refine_packs;
 
+use crate::strategies::auto::TSPackAutoStrategy;
+use crate::strategies::counter::TSPackCounterStrategy;
 use crate::strategies::delta::TSPackDeltaStrategy;
 use crate::strategies::delta_of_delta::TSPackDeltaOfDeltaStrategy;
 use crate::strategies::run_length::TSPackRunLengthStrategy;
 use crate::strategies::simple_8b::TSPackSimple8bStrategy;
 use crate::strategies::xor_gorilla::TSPackXorGorillaStrategy;
 
+/// Raw samples entering the strategy chain, either as tuple rows or as
+/// timestamp / value columns. Strategies read both through [`TSSampleSource`].
 #[derive(Debug, Clone)]
-pub enum Representation {
-    // Raw samples: (ts, value)
-    Raw(Vec<TSSamples>),
-    // Packed ranges: ((start_ts, end_ts), value)
-    Packed(Vec<TSPackedSamples>),
+pub enum RawSamples<'a> {
+    Rows(Cow<'a, [TSSamples]>),
+    Columns(TSColumns<'a>),
 }
 
-pub fn split_into_windows(samples: &[TSSamples], micro_window: u64) -> Vec<Vec<TSSamples>> {
-    if samples.is_empty() {
-        return Vec::new();
+impl RawSamples<'_> {
+    /// Sub-view of the samples in `range`, without copying.
+    pub fn slice(&self, range: Range<usize>) -> RawSamples<'_> {
+        match self {
+            RawSamples::Rows(rows) => RawSamples::Rows(Cow::Borrowed(&rows[range])),
+            RawSamples::Columns(columns) => RawSamples::Columns(columns.slice(range)),
+        }
     }
+}
 
-    let mut windows: Vec<Vec<TSSamples>> = Vec::new();
-    let mut current: Vec<TSSamples> = Vec::new();
-
-    let mut window_start_ts = samples[0].0;
-    let window_len_seconds = (micro_window as f64) / 1_000_000.0;
-
-    for &(ts, val) in samples {
-        if ts - window_start_ts <= window_len_seconds {
-            current.push((ts, val));
-        } else {
-            if !current.is_empty() {
-                windows.push(current.clone());
-                current.clear();
-            }
-            window_start_ts = ts;
-            current.push((ts, val));
+impl TSSampleSource for RawSamples<'_> {
+    fn len(&self) -> usize {
+        match self {
+            RawSamples::Rows(rows) => rows.len(),
+            RawSamples::Columns(columns) => columns.len(),
         }
     }
 
-    if !current.is_empty() {
-        windows.push(current);
+    #[inline]
+    fn sample(&self, index: usize) -> TSSamples {
+        match self {
+            RawSamples::Rows(rows) => rows[index],
+            RawSamples::Columns(columns) => columns.sample(index),
+        }
     }
+}
+
+#[derive(Debug, Clone)]
+pub enum Representation<'a> {
+    // Raw samples: (ts, value), borrowed from the input window until a strategy packs them
+    Raw(RawSamples<'a>),
+    // Packed ranges: ((start_ts, end_ts), value)
+    Packed(Vec<TSPackedSamples>),
+}
 
-    windows
+/// Splits sorted samples into windows of `micro_window` microseconds, each a
+/// borrowed sub-slice of `samples`.
+pub fn split_into_windows(samples: &[TSSamples], micro_window: u64) -> Vec<&[TSSamples]> {
+    samples
+        .window_ranges(micro_window)
+        .into_iter()
+        .map(|range| &samples[range])
+        .collect()
 }
 
-pub fn apply_strategy(
-    representation: Representation,
+/// Packs raw samples with a single strategy, reading them through
+/// [`TSSampleSource`] so rows and columns need no conversion.
+pub fn pack_samples<S: TSSampleSource + ?Sized>(
+    samples: &S,
     strategy: &TSPackStrategyType,
     precision_epsilon: f64,
-) -> Representation {
+) -> Vec<TSPackedSamples> {
     match strategy {
-        TSPackStrategyType::TSPackSimilarValuesStrategy => match representation {
-            Representation::Raw(samples) => {
-                Representation::Packed(similar_values_pack(&samples, precision_epsilon))
-            }
-            Representation::Packed(packs) => {
-                let merged = merge_adjacent_equal_value_ranges(packs, precision_epsilon);
-                Representation::Packed(merged)
-            }
-        },
+        TSPackStrategyType::TSPackSimilarValuesStrategy => {
+            similar_values_pack(samples, precision_epsilon)
+        }
         TSPackStrategyType::TSPackMeanStrategy {
             values_compression_percent,
-        } => match representation {
-            Representation::Raw(samples) => Representation::Packed(mean_pack(
-                &samples,
-                *values_compression_percent,
-                precision_epsilon,
-            )),
-            Representation::Packed(packs) => Representation::Packed(mean_refine_packs(
-                packs,
-                *values_compression_percent,
-                precision_epsilon,
-            )),
-        },
-        TSPackStrategyType::TSPackXorStrategy => match representation {
-            Representation::Raw(samples) => {
-                Representation::Packed(TSPackXorGorillaStrategy::pack(&samples))
-            }
-            Representation::Packed(packs) => {
-                let raw = TSPackXorGorillaStrategy::unpack(&packs);
-                Representation::Packed(TSPackXorGorillaStrategy::pack(&raw))
-            }
-        },
-        TSPackStrategyType::TSPackDeltaStrategy => match representation {
-            Representation::Raw(samples) => {
-                let packed = TSPackDeltaStrategy::pack(&samples);
-                Representation::Packed(packed)
-            }
-            Representation::Packed(packs) => {
-                // unpack -> repack (same pattern as XOR)
-                let raw = TSPackDeltaStrategy::unpack(&packs);
-                let repacked = TSPackDeltaStrategy::pack(&raw);
-                Representation::Packed(repacked)
-            }
-        },
-        TSPackStrategyType::TSPackDeltaOfDeltaStrategy => match representation {
-            Representation::Raw(samples) => {
-                Representation::Packed(TSPackDeltaOfDeltaStrategy::pack(&samples))
-            }
-            Representation::Packed(packs) => {
-                let raw = TSPackDeltaOfDeltaStrategy::unpack(&packs);
-                Representation::Packed(TSPackDeltaOfDeltaStrategy::pack(&raw))
-            }
-        },
-        TSPackStrategyType::TSPackRunLengthStrategy => match representation {
-            Representation::Raw(samples) => {
-                Representation::Packed(TSPackRunLengthStrategy::pack(&samples))
-            }
-            Representation::Packed(packs) => {
-                let raw = TSPackRunLengthStrategy::unpack(&packs);
-                Representation::Packed(TSPackRunLengthStrategy::pack(&raw))
-            }
-        },
-        TSPackStrategyType::TSPackSimple8bStrategy => match representation {
-            Representation::Raw(samples) => {
-                Representation::Packed(TSPackSimple8bStrategy::pack(&samples, precision_epsilon))
+        } => mean_pack(samples, *values_compression_percent, precision_epsilon),
+        TSPackStrategyType::TSPackXorStrategy => TSPackXorGorillaStrategy::pack(samples),
+        TSPackStrategyType::TSPackDeltaStrategy => TSPackDeltaStrategy::pack(samples),
+        TSPackStrategyType::TSPackDeltaOfDeltaStrategy => TSPackDeltaOfDeltaStrategy::pack(samples),
+        TSPackStrategyType::TSPackRunLengthStrategy => TSPackRunLengthStrategy::pack(samples),
+        TSPackStrategyType::TSPackSimple8bStrategy => {
+            TSPackSimple8bStrategy::pack(samples, precision_epsilon)
+        }
+        TSPackStrategyType::TSPackCounterStrategy => TSPackCounterStrategy::pack(samples),
+        TSPackStrategyType::TSPackAutoStrategy {
+            candidates,
+            max_error,
+        } => TSPackAutoStrategy::pack(samples, candidates, *max_error, precision_epsilon),
+    }
+}
+
+pub fn apply_strategy<'a>(
+    representation: Representation<'a>,
+    strategy: &TSPackStrategyType,
+    precision_epsilon: f64,
+) -> Representation<'a> {
+    let packs = match representation {
+        Representation::Raw(samples) => {
+            return Representation::Packed(pack_samples(&samples, strategy, precision_epsilon));
+        }
+        Representation::Packed(packs) => packs,
+    };
+
+    Representation::Packed(match strategy {
+        TSPackStrategyType::TSPackSimilarValuesStrategy => {
+            merge_adjacent_equal_value_ranges(packs, precision_epsilon)
+        }
+        TSPackStrategyType::TSPackMeanStrategy {
+            values_compression_percent,
+        } => mean_refine_packs(packs, *values_compression_percent, precision_epsilon),
+        // bit-exact and run-length encodings: unpack -> repack
+        TSPackStrategyType::TSPackXorStrategy => {
+            TSPackXorGorillaStrategy::pack(&TSPackXorGorillaStrategy::unpack(&packs))
+        }
+        TSPackStrategyType::TSPackDeltaStrategy => {
+            TSPackDeltaStrategy::pack(&TSPackDeltaStrategy::unpack(&packs))
+        }
+        TSPackStrategyType::TSPackDeltaOfDeltaStrategy => {
+            TSPackDeltaOfDeltaStrategy::pack(&TSPackDeltaOfDeltaStrategy::unpack(&packs))
+        }
+        TSPackStrategyType::TSPackRunLengthStrategy => {
+            TSPackRunLengthStrategy::pack(&TSPackRunLengthStrategy::unpack(&packs))
+        }
+        TSPackStrategyType::TSPackSimple8bStrategy => {
+            let raw = TSPackSimple8bStrategy::unpack(&packs, precision_epsilon);
+            TSPackSimple8bStrategy::pack(&raw, precision_epsilon)
+        }
+        TSPackStrategyType::TSPackCounterStrategy => {
+            TSPackCounterStrategy::pack(&TSPackCounterStrategy::unpack(&packs))
+        }
+        TSPackStrategyType::TSPackAutoStrategy {
+            candidates,
+            max_error,
+        } => {
+            // Auto ends the chain, so earlier stages produced plain ranges
+            let raw = TSPackRunLengthStrategy::unpack(&packs);
+            TSPackAutoStrategy::pack(&raw, candidates, *max_error, precision_epsilon)
+        }
+    })
+}
+
+/// Runs the whole strategy chain over one window of raw samples.
+pub fn pack_window(
+    window_samples: &[TSSamples],
+    attributes: &TSPackAttributes,
+) -> Vec<TSPackedSamples> {
+    pack_window_with(
+        RawSamples::Rows(Cow::Borrowed(window_samples)),
+        attributes,
+        |_, _, _, _| {},
+    )
+}
+
+/// [`pack_window`] calling `on_stage(stage, entries, buffer_bytes, elapsed)`
+/// after every strategy of the chain.
+pub fn pack_window_with(
+    window_samples: RawSamples<'_>,
+    attributes: &TSPackAttributes,
+    mut on_stage: impl FnMut(usize, usize, usize, Duration),
+) -> Vec<TSPackedSamples> {
+    let mut current_representation = Representation::Raw(window_samples);
+
+    for (stage, strategy) in attributes.strategy_types.iter().enumerate() {
+        let started = Instant::now();
+        current_representation = apply_strategy(
+            current_representation,
+            strategy,
+            attributes.precision_epsilon,
+        );
+
+        let (entries, bytes) = match &current_representation {
+            Representation::Raw(RawSamples::Rows(Cow::Owned(samples))) => {
+                (samples.len(), samples.capacity() * size_of::<TSSamples>())
             }
+            Representation::Raw(samples) => (samples.len(), 0),
             Representation::Packed(packs) => {
-                let raw = TSPackSimple8bStrategy::unpack(&packs, precision_epsilon);
-                Representation::Packed(TSPackSimple8bStrategy::pack(&raw, precision_epsilon))
+                (packs.len(), packs.capacity() * size_of::<TSPackedSamples>())
             }
-        },
+        };
+        on_stage(stage, entries, bytes, started.elapsed());
+    }
+
+    finalize_to_packed(current_representation, finalize_epsilon(attributes))
+}
+
+/// Packed entries of one window with `(entries, buffer_bytes, elapsed)` of
+/// every strategy stage, for callers that report them after the fact.
+pub struct RecordedWindow {
+    pub packed: Vec<TSPackedSamples>,
+    pub stages: Vec<(usize, usize, Duration)>,
+    pub elapsed: Duration,
+}
+
+pub fn pack_window_recorded(
+    window_samples: RawSamples<'_>,
+    attributes: &TSPackAttributes,
+) -> RecordedWindow {
+    let started = Instant::now();
+    let mut stages = Vec::with_capacity(attributes.strategy_types.len());
+
+    let packed = pack_window_with(window_samples, attributes, |_, entries, bytes, elapsed| {
+        stages.push((entries, bytes, elapsed));
+    });
+
+    RecordedWindow {
+        packed,
+        stages,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// Re-runs the strategy chain over already packed ranges, so that ranges
+/// appended after the last one can join it.
+pub fn repack_ranges(
+    packs: Vec<TSPackedSamples>,
+    attributes: &TSPackAttributes,
+) -> Vec<TSPackedSamples> {
+    let mut current_representation = Representation::Packed(packs);
+
+    for strategy in &attributes.strategy_types {
+        current_representation = apply_strategy(
+            current_representation,
+            strategy,
+            attributes.precision_epsilon,
+        );
+    }
+
+    finalize_to_packed(current_representation, finalize_epsilon(attributes))
+}
+
+/// Decodes the packed entries of a single window with the decoder of the last
+/// strategy in the chain. Range strategies expand to start/end points.
+pub fn decode_window(
+    packed: &[TSPackedSamples],
+    strategies: &[TSPackStrategyType],
+    precision_epsilon: f64,
+) -> Vec<TSSamples> {
+    decode_window_iter(packed, strategies, precision_epsilon).collect()
+}
+
+/// Finds, for every window start timestamp, the index of the first packed
+/// range that ends at or after it. Used to re-align window offsets after
+/// ranges were merged across window boundaries.
+pub fn locate_window_offsets(packed: &[TSPackedSamples], window_starts: &mut [(f64, usize)]) {
+    for (start, offset) in window_starts.iter_mut() {
+        *offset = packed.partition_point(|((_, end), _)| *end < *start);
     }
 }
 
 pub fn finalize_to_packed(rep: Representation, eps: f64) -> Vec<TSPackedSamples> {
     match rep {
         Representation::Raw(samples) => samples
-            .into_iter()
+            .iter()
             .map(|(t, v)| ((t, t), round_to_precision(v, eps)))
             .collect(),
 
@@ -158,7 +289,7 @@ pub fn approx_equal(a: f64, b: f64, eps: f64) -> bool {
 }
 
 pub fn merge_adjacent_equal_value_ranges(
-    mut packed: Vec<TSPackedSamples>,
+    packed: Vec<TSPackedSamples>,
     eps: f64,
 ) -> Vec<TSPackedSamples> {
     if packed.is_empty() {
@@ -169,8 +300,8 @@ pub fn merge_adjacent_equal_value_ranges(
     let mut current = packed[0];
 
     for &next in &packed[1..] {
-        let ((cur_start, cur_end), cur_val) = current;
-        let ((next_start, next_end), next_val) = next;
+        let ((cur_start, _), cur_val) = current;
+        let ((_, next_end), next_val) = next;
 
         if approx_equal(cur_val, next_val, eps) {
             current = ((cur_start, next_end), cur_val);
@@ -184,17 +315,59 @@ pub fn merge_adjacent_equal_value_ranges(
     result
 }
 
+/// Whether `strategy` encodes points instead of ranges; it must end the chain.
+#[inline]
+pub fn is_bit_exact(strategy: &TSPackStrategyType) -> bool {
+    matches!(
+        strategy,
+        TSPackStrategyType::TSPackXorStrategy
+            | TSPackStrategyType::TSPackDeltaStrategy
+            | TSPackStrategyType::TSPackDeltaOfDeltaStrategy
+            | TSPackStrategyType::TSPackSimple8bStrategy
+            | TSPackStrategyType::TSPackCounterStrategy
+            | TSPackStrategyType::TSPackAutoStrategy { .. }
+    )
+}
+
 #[inline]
 pub fn uses_bit_exact_encoding(strategies: &[TSPackStrategyType]) -> bool {
-    strategies.iter().any(|strategy| {
-        matches!(
-            strategy,
-            TSPackStrategyType::TSPackXorStrategy
-                | TSPackStrategyType::TSPackDeltaStrategy
-                | TSPackStrategyType::TSPackDeltaOfDeltaStrategy
-                | TSPackStrategyType::TSPackSimple8bStrategy
-        )
-    })
+    strategies.iter().any(is_bit_exact)
+}
+
+/// Tolerance for matching reconstructed timestamps, which Simple-8b rounds to
+/// whole microseconds.
+pub const TIMESTAMP_TOLERANCE: f64 = 5e-7;
+
+/// Value of a reconstructed series at `ts`, holding the last point at or
+/// before it. `None` before the first point.
+pub fn step_hold_value(reconstructed: &[TSSamples], ts: f64) -> Option<f64> {
+    let index = reconstructed.partition_point(|(rts, _)| *rts <= ts + TIMESTAMP_TOLERANCE);
+    index.checked_sub(1).map(|i| reconstructed[i].1)
+}
+
+/// Largest absolute difference between each original sample and the
+/// step-hold value of the reconstruction at its timestamp.
+pub fn max_step_hold_error<S: TSSampleSource + ?Sized>(
+    original: &S,
+    reconstructed: &[TSSamples],
+) -> f64 {
+    original
+        .iter()
+        .map(|(ts, value)| match step_hold_value(reconstructed, ts) {
+            Some(held) if held.to_bits() == value.to_bits() => 0.0,
+            Some(held) => (held - value).abs(),
+            None => f64::INFINITY,
+        })
+        .fold(0.0, f64::max)
+}
+
+#[inline]
+pub fn finalize_epsilon(attributes: &TSPackAttributes) -> f64 {
+    if uses_bit_exact_encoding(&attributes.strategy_types) {
+        0.0
+    } else {
+        attributes.precision_epsilon
+    }
 }
 
 pub fn round_to_precision(value: f64, eps: f64) -> f64 {
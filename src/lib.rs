@@ -1,22 +1,95 @@
+pub mod aggregate;
+pub mod blocks;
+pub mod builder;
+pub mod columns;
+pub mod decode;
+pub mod grid;
 pub mod helpers;
+pub mod lookup;
+pub mod metrics;
+pub mod partitions;
+pub mod predicate;
+pub mod presets;
+pub mod query;
+pub mod rate;
+pub mod states;
+pub mod stats;
 pub mod strategies;
+pub mod threshold;
+pub mod tuning;
+pub mod validation;
+pub mod verify;
 
+#[cfg(test)]
+mod test_support;
+
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::mem::size_of;
+use std::ops::Range;
+use std::time::Duration;
+use std::time::Instant;
 use thiserror::Error;
 
-use crate::helpers::apply_strategy;
-use crate::helpers::finalize_to_packed;
+use crate::columns::TSSampleSource;
+use crate::helpers::decode_window;
+use crate::helpers::locate_window_offsets;
 use crate::helpers::merge_adjacent_equal_value_ranges;
-use crate::helpers::round_to_precision;
+use crate::helpers::pack_window;
+use crate::helpers::pack_window_recorded;
+use crate::helpers::repack_ranges;
 use crate::helpers::split_into_windows;
 use crate::helpers::uses_bit_exact_encoding;
-use crate::helpers::Representation;
-
+use crate::helpers::RawSamples;
+#[cfg(feature = "parallel")]
+use crate::helpers::RecordedWindow;
+use crate::validation::validate_attributes;
+use crate::validation::validate_samples;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+pub use crate::aggregate::TSPackAggregate;
+pub use crate::blocks::TSPackBlockContainer;
+pub use crate::blocks::TSPackBlockIndexEntry;
+pub use crate::builder::TSPackAttributesBuilder;
+pub use crate::columns::TSColumns;
+pub use crate::columns::TSPackTimestamps;
+pub use crate::columns::TSPackedColumns;
+pub use crate::decode::TSPackSeriesIter;
+pub use crate::grid::TSPackGapPolicy;
+pub use crate::grid::TSPackGridInterpolation;
+pub use crate::grid::TSPackGridMode;
+pub use crate::lookup::TSPackInterpolation;
+pub use crate::metrics::TSPackAlignment;
+pub use crate::metrics::TSPackErrorMetrics;
+pub use crate::metrics::TSPackSizeStats;
+pub use crate::partitions::TSPackBucket;
+pub use crate::partitions::TSPackBucketGranularity;
+pub use crate::partitions::TSPackPartitions;
+pub use crate::predicate::TSPackPredicate;
+pub use crate::presets::TSPackPresets;
+pub use crate::states::TSPackStateDuration;
+pub use crate::states::TSPackStateRun;
+pub use crate::states::TSPackStateStats;
+pub use crate::stats::TSPackObserver;
+pub use crate::stats::TSPackStageStats;
+pub use crate::stats::TSPackStats;
+pub use crate::strategies::auto::TSPackAutoStrategy;
+pub use crate::strategies::counter::TSPackCounterStrategy;
 pub use crate::strategies::delta::TSPackDeltaStrategy;
 pub use crate::strategies::delta_of_delta::TSPackDeltaOfDeltaStrategy;
 pub use crate::strategies::run_length::TSPackRunLengthStrategy;
 pub use crate::strategies::simple_8b::TSPackSimple8bStrategy;
 pub use crate::strategies::xor_gorilla::TSPackXorGorillaStrategy;
+pub use crate::threshold::TSPackCrossing;
+pub use crate::threshold::TSPackCrossingKind;
+pub use crate::threshold::TSPackThreshold;
+pub use crate::threshold::TSPackThresholdEvent;
+pub use crate::threshold::TSPackThresholdSide;
+pub use crate::tuning::TSPackErrorBudget;
+pub use crate::tuning::TSPackTuneResult;
+pub use crate::tuning::TSPackTuneSpace;
+pub use crate::verify::TSPackVerifyReport;
 
 // A single raw sample: (timestamp_seconds, value)
 pub type TSSamples = (f64, f64);
@@ -24,7 +97,7 @@ pub type TSSamples = (f64, f64);
 // A single packed sample: ((start_seconds, end_seconds), value)
 pub type TSPackedSamples = ((f64, f64), f64);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TSPackPrecisionDataType {
     MilisValues,
     WavDerivedAudio,
@@ -45,7 +118,7 @@ impl TSPackPrecisionDataType {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TSPackStrategyType {
     TSPackSimilarValuesStrategy,
     TSPackMeanStrategy {
@@ -64,36 +137,106 @@ pub enum TSPackStrategyType {
 
     /// Simple-8b compression for scaled integer deltas.
     TSPackSimple8bStrategy,
+
+    /// Monotonic counter: non-negative increments, resets stored as raw values.
+    TSPackCounterStrategy,
+
+    /// Picks, per window, the candidate with the fewest packed entries whose
+    /// reconstruction error stays within `max_error`.
+    TSPackAutoStrategy {
+        candidates: Vec<TSPackStrategyType>,
+        max_error: f64,
+    },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TSPackAttributes {
     pub strategy_types: Vec<TSPackStrategyType>,
     pub microseconds_time_window: u64,
     pub precision_epsilon: f64,
 }
 
+impl TSPackAttributes {
+    pub fn builder() -> TSPackAttributesBuilder {
+        TSPackAttributesBuilder::new()
+    }
+
+    /// Checks window, epsilon and the strategy chain without packing anything.
+    pub fn validate(&self) -> Result<(), TSPackError> {
+        validate_attributes(self)
+    }
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum TSPackError {
     #[error("microseconds_time_window must be > 0")]
     InvalidWindow,
+    #[error("precision_epsilon must be finite and >= 0, got {0}")]
+    InvalidEpsilon(f64),
+    #[error("values_compression_percent must be <= 100, got {percent} at position {position}")]
+    InvalidCompressionPercent { position: usize, percent: u8 },
+    #[error("strategy_types must not be empty")]
+    EmptyStrategyChain,
+    #[error(
+        "strategy at position {position} follows a bit-exact encoding, which must end the chain"
+    )]
+    InvalidStrategyChain { position: usize },
+    #[error("sample at index {index} has a non-finite timestamp")]
+    NonFiniteTimestamp { index: usize },
+    #[error("sample at index {index} has a non-finite value not supported by the strategy chain")]
+    NonFiniteValue { index: usize },
+    #[error("max_error must be finite and >= 0, got {0}")]
+    InvalidErrorBound(f64),
+    #[error("auto strategy at position {position} needs candidates and cannot nest another auto strategy")]
+    InvalidAutoCandidates { position: usize },
+    #[error("reconstruction error {error} exceeds the budget of {budget}")]
+    ErrorBudgetExceeded { error: f64, budget: f64 },
+    #[error("invalid pipeline spec: {0}")]
+    InvalidSpec(String),
+    #[error("timestamp column has {timestamps} entries but value column has {values}")]
+    ColumnLengthMismatch { timestamps: usize, values: usize },
+    #[error(
+        "verification failed: {} samples exceed the bound of {} (max error {})",
+        .report.violations.len(),
+        .report.bound,
+        .report.max_error
+    )]
+    VerificationFailed { report: Box<TSPackVerifyReport> },
+    #[error("threshold hysteresis must be finite and >= 0, got {0}")]
+    InvalidHysteresis(f64),
+    #[error("UTC offset must be within +/-14 hours, got {0} seconds")]
+    InvalidUtcOffset(i32),
+    #[error("append requires a series packed with pack() first")]
+    NotPacked,
+    #[error("sample at {timestamp}s is older than the last packed sample at {last_timestamp}s")]
+    OutOfOrderSample { timestamp: f64, last_timestamp: f64 },
+    #[error("late sample at {timestamp}s is older than the lateness watermark at {watermark}s")]
+    LateSampleRejected { timestamp: f64, watermark: f64 },
+    #[error("time range [{start}, {end}] must be finite and fit in memory")]
+    InvalidTimeRange { start: f64, end: f64 },
 }
 
-#[derive(Debug, Clone)]
+/// What happens to samples older than the lateness watermark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TSPackLatePolicy {
+    /// Fail the whole insert with [`TSPackError::LateSampleRejected`].
+    Reject,
+    /// Keep them aside, see [`TimeSeriesDataPacker::take_late_samples`].
+    SideBuffer,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct TimeSeriesDataPacker {
     attributes: Option<TSPackAttributes>,
-    original_samples: Vec<TSSamples>,
     packed_samples: Vec<TSPackedSamples>,
-}
-
-impl Default for TimeSeriesDataPacker {
-    fn default() -> Self {
-        Self {
-            attributes: None,
-            original_samples: Vec::new(),
-            packed_samples: Vec::new(),
-        }
-    }
+    // (window_start_ts, offset of the window's first entry in packed_samples)
+    window_starts: Vec<(f64, usize)>,
+    // (watermark in microseconds behind the last packed sample, policy)
+    lateness: Option<(u64, TSPackLatePolicy)>,
+    late_samples: Vec<TSSamples>,
+    sample_count: usize,
+    #[cfg(feature = "parallel")]
+    parallel_windows: bool,
 }
 
 impl TimeSeriesDataPacker {
@@ -101,76 +244,502 @@ impl TimeSeriesDataPacker {
         Self::default()
     }
 
+    /// Limits how far behind the last packed sample [`Self::insert`] accepts
+    /// late samples. Without a watermark every late sample is merged.
+    pub fn with_lateness(mut self, microseconds_watermark: u64, policy: TSPackLatePolicy) -> Self {
+        self.lateness = Some((microseconds_watermark, policy));
+        self
+    }
+
+    /// Packs the windows of [`Self::pack`] and its variants on the rayon
+    /// thread pool. The output is identical to sequential packing: windows are
+    /// concatenated and merged in order once all of them are packed.
+    #[cfg(feature = "parallel")]
+    pub fn with_parallel_windows(mut self, enabled: bool) -> Self {
+        self.parallel_windows = enabled;
+        self
+    }
+
+    /// Returns (and clears) late samples routed to the side buffer.
+    pub fn take_late_samples(&mut self) -> Vec<TSSamples> {
+        std::mem::take(&mut self.late_samples)
+    }
+
+    /// Packs `samples` and returns the packed series, which the packer keeps
+    /// for queries. Call `.to_vec()` on the result or
+    /// [`Self::into_packed_samples`] to take ownership of it.
     pub fn pack(
+        &mut self,
+        samples: Vec<TSSamples>,
+        attributes: TSPackAttributes,
+    ) -> Result<&[TSPackedSamples], TSPackError> {
+        self.pack_observed(samples, attributes, &mut ())
+            .map(|(packed, _)| packed)
+    }
+
+    /// [`Self::pack`] that also returns what happened inside the pipeline.
+    pub fn pack_with_stats(
+        &mut self,
+        samples: Vec<TSSamples>,
+        attributes: TSPackAttributes,
+    ) -> Result<(&[TSPackedSamples], TSPackStats), TSPackError> {
+        self.pack_observed(samples, attributes, &mut ())
+    }
+
+    /// [`Self::pack_with_stats`] reporting every window and strategy stage to
+    /// `observer` as it is packed.
+    pub fn pack_observed(
         &mut self,
         mut samples: Vec<TSSamples>,
         attributes: TSPackAttributes,
-    ) -> Result<Vec<TSPackedSamples>, TSPackError> {
-        if attributes.microseconds_time_window == 0 {
-            return Err(TSPackError::InvalidWindow);
-        }
+        observer: &mut dyn TSPackObserver,
+    ) -> Result<(&[TSPackedSamples], TSPackStats), TSPackError> {
+        Self::validate(&samples, &attributes)?;
 
         samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        let stats = self.pack_sorted(
+            RawSamples::Rows(Cow::Borrowed(&samples)),
+            attributes,
+            observer,
+        );
 
-        let windows = split_into_windows(&samples, attributes.microseconds_time_window);
+        Ok((&self.packed_samples, stats))
+    }
 
-        let mut packed_all: Vec<TSPackedSamples> = Vec::new();
+    /// Borrowing variant of [`Self::pack`]: windows are sub-slices of
+    /// `samples`, which are only copied when they are not sorted by timestamp.
+    pub fn pack_slice(
+        &mut self,
+        samples: &[TSSamples],
+        attributes: TSPackAttributes,
+    ) -> Result<&[TSPackedSamples], TSPackError> {
+        Self::validate(samples, &attributes)?;
 
-        let bit_exact = uses_bit_exact_encoding(&attributes.strategy_types);
-        let finalize_epsilon = if bit_exact {
-            0.0
+        let sorted: Cow<[TSSamples]> = if samples.windows(2).all(|pair| pair[0].0 <= pair[1].0) {
+            Cow::Borrowed(samples)
         } else {
-            attributes.precision_epsilon
+            let mut owned = samples.to_vec();
+            owned.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+            Cow::Owned(owned)
         };
+        self.pack_sorted(RawSamples::Rows(sorted), attributes, &mut ());
 
-        for window_samples in windows {
-            let mut current_representation = Representation::Raw(window_samples);
+        Ok(&self.packed_samples)
+    }
+
+    /// Packed entries of the current series.
+    pub fn packed_samples(&self) -> &[TSPackedSamples] {
+        &self.packed_samples
+    }
+
+    /// Consumes the packer, moving out the packed entries.
+    pub fn into_packed_samples(self) -> Vec<TSPackedSamples> {
+        self.packed_samples
+    }
+
+    /// Packs columnar input: `f64` timestamps in seconds or `i64` timestamps
+    /// in microseconds, plus a value column of the same length. Strategies read
+    /// the columns directly; they are only copied when not sorted by timestamp.
+    ///
+    /// The output is not written column by column: the packed tuples the
+    /// packer keeps are converted with [`Self::packed_columns`], which copies
+    /// them into three new arrays.
+    pub fn pack_columns<T: TSPackTimestamps + ?Sized>(
+        &mut self,
+        timestamps: &T,
+        values: &[f64],
+        attributes: TSPackAttributes,
+    ) -> Result<TSPackedColumns, TSPackError> {
+        let columns = TSColumns::new(timestamps, values)?;
+        Self::validate(&columns, &attributes)?;
 
-            for strategy in &attributes.strategy_types {
-                current_representation = apply_strategy(
-                    current_representation,
-                    strategy,
-                    attributes.precision_epsilon,
-                );
+        if columns.is_sorted() {
+            self.pack_sorted(RawSamples::Columns(columns), attributes, &mut ());
+        } else {
+            let mut order: Vec<usize> = (0..columns.len()).collect();
+            order.sort_by(|a, b| {
+                columns
+                    .timestamp(*a)
+                    .partial_cmp(&columns.timestamp(*b))
+                    .unwrap_or(Ordering::Equal)
+            });
+            let sorted_timestamps: Vec<f64> = order.iter().map(|i| columns.timestamp(*i)).collect();
+            let sorted_values: Vec<f64> = order.iter().map(|i| values[*i]).collect();
+
+            let sorted = TSColumns::new(&sorted_timestamps[..], &sorted_values)?;
+            self.pack_sorted(RawSamples::Columns(sorted), attributes, &mut ());
+        }
+
+        Ok(self.packed_columns())
+    }
+
+    /// Packed entries of the current series, copied into start, end and value
+    /// arrays.
+    pub fn packed_columns(&self) -> TSPackedColumns {
+        TSPackedColumns::from(self.packed_samples.as_slice())
+    }
+
+    fn pack_sorted(
+        &mut self,
+        samples: RawSamples<'_>,
+        attributes: TSPackAttributes,
+        observer: &mut dyn TSPackObserver,
+    ) -> TSPackStats {
+        let started = Instant::now();
+        let windows = samples.window_ranges(attributes.microseconds_time_window);
+
+        let mut stats = TSPackStats {
+            samples: samples.len(),
+            windows: windows.len(),
+            stages: attributes
+                .strategy_types
+                .iter()
+                .map(|strategy| TSPackStageStats {
+                    strategy: strategy.clone(),
+                    entries: 0,
+                    elapsed: Duration::ZERO,
+                })
+                .collect(),
+            bytes_allocated: windows.capacity() * size_of::<Range<usize>>(),
+            ..TSPackStats::default()
+        };
+
+        let mut packed_all: Vec<TSPackedSamples> = Vec::new();
+        let mut window_starts: Vec<(f64, usize)> = Vec::with_capacity(windows.len());
+
+        let pack_range =
+            |range: &Range<usize>| pack_window_recorded(samples.slice(range.clone()), &attributes);
+
+        // windows are independent; results are consumed in window order either way
+        #[cfg(feature = "parallel")]
+        let recorded: Box<dyn Iterator<Item = RecordedWindow>> = if self.parallel_windows {
+            Box::new(
+                windows
+                    .par_iter()
+                    .map(pack_range)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )
+        } else {
+            Box::new(windows.iter().map(pack_range))
+        };
+        #[cfg(not(feature = "parallel"))]
+        let recorded = windows.iter().map(pack_range);
+
+        for (window, (range, recorded)) in windows.iter().zip(recorded).enumerate() {
+            let window_start = samples.timestamp(range.start);
+            window_starts.push((window_start, packed_all.len()));
+
+            for (stage, &(entries, bytes, elapsed)) in recorded.stages.iter().enumerate() {
+                let stage_stats = &mut stats.stages[stage];
+                stage_stats.entries += entries;
+                stage_stats.elapsed += elapsed;
+                stats.bytes_allocated += bytes;
+                observer.on_stage(window, stage, &stage_stats.strategy, entries, elapsed);
             }
 
-            let packed = finalize_to_packed(current_representation, finalize_epsilon);
-            packed_all.extend(packed);
+            observer.on_window(
+                window,
+                window_start,
+                range.len(),
+                recorded.packed.len(),
+                recorded.elapsed,
+            );
+            packed_all.extend(recorded.packed);
         }
 
-        let merged = if bit_exact {
+        stats.window_entries = packed_all.len();
+        stats.bytes_allocated += packed_all.capacity() * size_of::<TSPackedSamples>();
+
+        let merged = if uses_bit_exact_encoding(&attributes.strategy_types) {
             packed_all
         } else {
-            merge_adjacent_equal_value_ranges(packed_all, attributes.precision_epsilon)
+            let merge_started = Instant::now();
+            let merged =
+                merge_adjacent_equal_value_ranges(packed_all, attributes.precision_epsilon);
+            locate_window_offsets(&merged, &mut window_starts);
+
+            stats.merges = stats.window_entries - merged.len();
+            stats.merge_elapsed = merge_started.elapsed();
+            stats.bytes_allocated += merged.capacity() * size_of::<TSPackedSamples>();
+            observer.on_merge(stats.window_entries, merged.len(), stats.merge_elapsed);
+            merged
         };
 
-        self.attributes = Some(attributes.clone());
-        self.original_samples = samples;
-        self.packed_samples = merged.clone();
+        stats.entries = merged.len();
+
+        self.attributes = Some(attributes);
+        self.packed_samples = merged;
+        self.window_starts = window_starts;
+        self.sample_count = samples.len();
 
-        Ok(merged)
+        stats.elapsed = started.elapsed();
+        observer.on_finish(&stats);
+
+        stats
     }
 
-    pub fn unpack(&self) -> (Option<TSPackAttributes>, Vec<TSSamples>) {
-        let mut result: Vec<TSSamples> = Vec::new();
+    /// Dry run of [`Self::pack`]: validates the attributes and the samples.
+    pub fn validate<S: TSSampleSource + ?Sized>(
+        samples: &S,
+        attributes: &TSPackAttributes,
+    ) -> Result<(), TSPackError> {
+        validate_attributes(attributes)?;
+        validate_samples(samples, attributes)
+    }
+
+    /// Appends new samples to an already packed series without the raw history.
+    ///
+    /// Samples that still fall into the last time window continue its open range
+    /// (or its delta / XOR anchor); later samples open new windows. Only the last
+    /// window is decoded and re-encoded, and only the ranges from the entry before
+    /// it on are re-merged, so the cost does not grow with history. The packed
+    /// series is returned by reference.
+    ///
+    /// Mean chains average the ranges of the last window again together with the
+    /// new samples, so appending in chunks can give a different series than
+    /// packing all samples at once. Other chains give the same series.
+    pub fn append(
+        &mut self,
+        mut samples: Vec<TSSamples>,
+    ) -> Result<&[TSPackedSamples], TSPackError> {
+        let attributes = self.attributes.clone().ok_or(TSPackError::NotPacked)?;
+        validate_samples(&samples, &attributes)?;
+
+        samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let Some(&(tail_start, tail_offset)) = self.window_starts.last() else {
+            return self.pack_slice(&samples, attributes);
+        };
+
+        if samples.is_empty() {
+            return Ok(&self.packed_samples);
+        }
+
+        let added = samples.len();
+        let bit_exact = uses_bit_exact_encoding(&attributes.strategy_types);
+        let mut packed = std::mem::take(&mut self.packed_samples);
+        let tail = packed.split_off(tail_offset);
+        let mut tail_samples = decode_window(
+            &tail,
+            &attributes.strategy_types,
+            attributes.precision_epsilon,
+        );
+
+        let last_timestamp = tail_samples.last().map(|(ts, _)| *ts).unwrap_or(tail_start);
+        if samples[0].0 < last_timestamp {
+            packed.extend(tail);
+            self.packed_samples = packed;
+            return Err(TSPackError::OutOfOrderSample {
+                timestamp: samples[0].0,
+                last_timestamp,
+            });
+        }
+
+        let window_len_seconds = (attributes.microseconds_time_window as f64) / 1_000_000.0;
+        let split = samples.partition_point(|(ts, _)| ts - tail_start <= window_len_seconds);
+        let later_samples = samples.split_off(split);
+
+        // entries before it are already merged and cannot change
+        let boundary = packed.len().saturating_sub(1);
 
-        let eps = self
-            .attributes
-            .as_ref()
-            .map(|a| a.precision_epsilon)
-            .unwrap_or(0.0);
+        if samples.is_empty() {
+            packed.extend(tail);
+        } else if bit_exact {
+            tail_samples.extend(samples);
+            packed.extend(pack_window(&tail_samples, &attributes));
+        } else {
+            let mut ranges = tail;
+            ranges.extend(pack_window(&samples, &attributes));
+            packed.extend(repack_ranges(ranges, &attributes));
+        }
 
-        for &((start, end), value) in &self.packed_samples {
-            let rounded_value = round_to_precision(value, eps);
+        for window_samples in
+            split_into_windows(&later_samples, attributes.microseconds_time_window)
+        {
+            self.window_starts.push((window_samples[0].0, packed.len()));
+            packed.extend(pack_window(window_samples, &attributes));
+        }
 
-            result.push((start, rounded_value));
+        if !bit_exact {
+            let unmerged = packed.split_off(boundary);
+            packed.extend(merge_adjacent_equal_value_ranges(
+                unmerged,
+                attributes.precision_epsilon,
+            ));
+
+            let affected = self
+                .window_starts
+                .partition_point(|(_, offset)| *offset < tail_offset);
+            locate_window_offsets(&packed, &mut self.window_starts[affected..]);
+        }
 
-            if end != start {
-                result.push((end, rounded_value));
+        self.packed_samples = packed;
+        self.sample_count += added;
+
+        Ok(&self.packed_samples)
+    }
+
+    /// Inserts samples in any order into an already packed series.
+    ///
+    /// Samples newer than the last packed one are appended. Late samples, at or
+    /// before its timestamp, are merged into the windows they belong to: only
+    /// those windows are decoded, merged with the late data and re-encoded,
+    /// then neighbouring ranges are re-merged. Samples behind the lateness watermark follow its policy.
+    pub fn insert(
+        &mut self,
+        mut samples: Vec<TSSamples>,
+    ) -> Result<&[TSPackedSamples], TSPackError> {
+        let attributes = self.attributes.clone().ok_or(TSPackError::NotPacked)?;
+        validate_samples(&samples, &attributes)?;
+
+        samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let Some(last_timestamp) = self.last_timestamp() else {
+            return self.pack(samples, attributes);
+        };
+
+        let newer = samples.split_off(samples.partition_point(|(ts, _)| *ts <= last_timestamp));
+        let mut late = samples;
+
+        if let Some((microseconds_watermark, policy)) = self.lateness {
+            let watermark = last_timestamp - (microseconds_watermark as f64) / 1_000_000.0;
+            let too_late = late.partition_point(|(ts, _)| *ts < watermark);
+
+            if too_late > 0 {
+                match policy {
+                    TSPackLatePolicy::Reject => {
+                        return Err(TSPackError::LateSampleRejected {
+                            timestamp: late[0].0,
+                            watermark,
+                        });
+                    }
+                    TSPackLatePolicy::SideBuffer => {
+                        self.late_samples.extend(late.drain(..too_late));
+                    }
+                }
             }
         }
 
-        (self.attributes.clone(), result)
+        if !late.is_empty() {
+            self.merge_late_samples(late, &attributes);
+        }
+
+        if newer.is_empty() {
+            Ok(&self.packed_samples)
+        } else {
+            self.append(newer)
+        }
+    }
+
+    fn last_timestamp(&self) -> Option<f64> {
+        let attributes = self.attributes.as_ref()?;
+        let &(_, tail_offset) = self.window_starts.last()?;
+
+        decode_window(
+            &self.packed_samples[tail_offset..],
+            &attributes.strategy_types,
+            attributes.precision_epsilon,
+        )
+        .last()
+        .map(|(ts, _)| *ts)
+    }
+
+    fn merge_late_samples(&mut self, late: Vec<TSSamples>, attributes: &TSPackAttributes) {
+        let window_of = |ts: f64| {
+            self.window_starts
+                .partition_point(|(start, _)| *start <= ts)
+                .saturating_sub(1)
+        };
+        let first = window_of(late[0].0);
+        let last = window_of(late[late.len() - 1].0);
+
+        let bit_exact = uses_bit_exact_encoding(&attributes.strategy_types);
+        let slice_start = self.window_starts[first].1;
+        let slice_end = match self.window_starts.get(last + 1) {
+            None => self.packed_samples.len(),
+            Some(&(_, next_offset)) if bit_exact => next_offset,
+            // ranges spanning into the next window are decoded along with it
+            Some(&(next_start, _)) => self
+                .packed_samples
+                .partition_point(|((start, _), _)| *start < next_start),
+        };
+
+        let mut samples: Vec<TSSamples> = if bit_exact {
+            (first..=last)
+                .flat_map(|window| {
+                    let from = self.window_starts[window].1;
+                    let to = self
+                        .window_starts
+                        .get(window + 1)
+                        .map(|(_, offset)| *offset)
+                        .unwrap_or(self.packed_samples.len());
+                    decode_window(
+                        &self.packed_samples[from..to],
+                        &attributes.strategy_types,
+                        attributes.precision_epsilon,
+                    )
+                })
+                .collect()
+        } else {
+            decode_window(
+                &self.packed_samples[slice_start..slice_end],
+                &attributes.strategy_types,
+                attributes.precision_epsilon,
+            )
+        };
+
+        self.sample_count += late.len();
+        samples.extend(late);
+        samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let mut encoded: Vec<TSPackedSamples> = Vec::new();
+        let mut window_starts: Vec<(f64, usize)> = Vec::new();
+
+        for window_samples in split_into_windows(&samples, attributes.microseconds_time_window) {
+            window_starts.push((window_samples[0].0, slice_start + encoded.len()));
+            encoded.extend(pack_window(window_samples, attributes));
+        }
+
+        let shift = encoded.len() as isize - (slice_end - slice_start) as isize;
+        self.packed_samples.splice(slice_start..slice_end, encoded);
+
+        let following = self.window_starts.split_off(last + 1);
+        self.window_starts.truncate(first);
+        self.window_starts.extend(window_starts);
+        self.window_starts.extend(
+            following
+                .into_iter()
+                .map(|(start, offset)| (start, (offset as isize + shift) as usize)),
+        );
+
+        if !bit_exact {
+            self.packed_samples = merge_adjacent_equal_value_ranges(
+                std::mem::take(&mut self.packed_samples),
+                attributes.precision_epsilon,
+            );
+            locate_window_offsets(&self.packed_samples, &mut self.window_starts);
+        }
+    }
+
+    /// Raw vs encoded bytes of the packed series, by timestamps and values.
+    pub fn size_stats(&self) -> TSPackSizeStats {
+        TSPackSizeStats::new(self.sample_count, &self.packed_samples)
+    }
+
+    /// Reconstructs samples through the decoder of the strategy chain, window by
+    /// window. Unlike [`Self::unpack`], XOR / Delta / Delta-of-Delta / Simple-8b
+    /// payloads come back as original values; range strategies expand to their
+    /// start/end points.
+    pub fn decode(&self) -> Vec<TSSamples> {
+        self.iter_decode().collect()
+    }
+
+    /// Expands packed ranges to their start/end points. Windows packed by
+    /// [`TSPackStrategyType::TSPackAutoStrategy`] are decoded with the strategy
+    /// recorded for each of them.
+    pub fn unpack(&self) -> (Option<TSPackAttributes>, Vec<TSSamples>) {
+        (self.attributes.clone(), self.iter_unpack().collect())
     }
 }
 
@@ -507,4 +1076,409 @@ mod tests {
 
         assert_eq!(result, Err(TSPackError::InvalidWindow));
     }
+
+    #[test]
+    fn test_append_continues_open_similar_values_range() {
+        let samples = vec![
+            (0.0, 100.0),
+            (0.1, 100.0),
+            (0.2, 101.0),
+            (0.3, 101.0),
+            (1.5, 101.0),
+            (1.6, 102.0),
+        ];
+
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackSimilarValuesStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: TSPackPrecisionDataType::IoTSensors.epsilon(),
+        };
+
+        let mut full = TimeSeriesDataPacker::new();
+        let expected = full.pack(samples.clone(), attrs.clone()).unwrap();
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer.pack(samples[..3].to_vec(), attrs).unwrap();
+        packer.append(samples[3..4].to_vec()).unwrap();
+        let appended = packer.append(samples[4..].to_vec()).unwrap();
+
+        assert_eq!(expected, appended);
+        assert_eq!(appended[1], ((0.2, 1.5), 101.0));
+    }
+
+    #[test]
+    fn test_append_in_chunks_matches_a_single_pack() {
+        // plateaus of 7 samples, 0.1 s apart, spanning several 1 s windows
+        let samples: Vec<TSSamples> = (0..60).map(|i| (i as f64 * 0.1, (i / 7) as f64)).collect();
+
+        for strategy in [
+            TSPackStrategyType::TSPackSimilarValuesStrategy,
+            TSPackStrategyType::TSPackRunLengthStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+        ] {
+            let attrs = TSPackAttributes {
+                strategy_types: vec![strategy.clone()],
+                microseconds_time_window: 1_000_000,
+                precision_epsilon: 0.001,
+            };
+
+            let mut full = TimeSeriesDataPacker::new();
+            full.pack(samples.clone(), attrs.clone()).unwrap();
+
+            let mut packer = TimeSeriesDataPacker::new();
+            packer.pack(samples[..5].to_vec(), attrs).unwrap();
+            for chunk in samples[5..].chunks(4) {
+                packer.append(chunk.to_vec()).unwrap();
+            }
+
+            assert_eq!(packer.packed_samples, full.packed_samples, "{strategy:?}");
+            assert_eq!(packer.window_starts, full.window_starts, "{strategy:?}");
+        }
+    }
+
+    #[test]
+    fn test_append_continues_xor_anchor_of_last_window() {
+        let samples = vec![
+            (0.0, 10.0),
+            (0.1, 20.0),
+            (0.2, 30.0),
+            (0.3, 25.0),
+            (2.0, 1.0),
+        ];
+
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackXorStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.0,
+        };
+
+        let mut full = TimeSeriesDataPacker::new();
+        let expected = full.pack(samples.clone(), attrs.clone()).unwrap();
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer.pack(samples[..2].to_vec(), attrs).unwrap();
+        let appended = packer.append(samples[2..].to_vec()).unwrap();
+
+        assert_eq!(expected, appended);
+    }
+
+    #[test]
+    fn test_append_errors() {
+        let mut packer = TimeSeriesDataPacker::new();
+        assert_eq!(packer.append(vec![(0.0, 1.0)]), Err(TSPackError::NotPacked));
+
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackDeltaStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.0,
+        };
+        let packed = packer
+            .pack(vec![(1.0, 1.0), (2.0, 2.0)], attrs)
+            .unwrap()
+            .to_vec();
+
+        assert_eq!(
+            packer.append(vec![(1.5, 3.0)]),
+            Err(TSPackError::OutOfOrderSample {
+                timestamp: 1.5,
+                last_timestamp: 2.0,
+            })
+        );
+        assert_eq!(packer.append(vec![]), Ok(packed.as_slice()));
+    }
+
+    #[test]
+    fn test_insert_merges_late_samples_into_their_windows() {
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackSimilarValuesStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: TSPackPrecisionDataType::IoTSensors.epsilon(),
+        };
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer
+            .pack(vec![(0.0, 1.0), (0.4, 1.0), (2.0, 3.0), (2.5, 3.0)], attrs)
+            .unwrap();
+
+        let packed = packer.insert(vec![(3.0, 3.0), (0.2, 5.0)]).unwrap();
+
+        assert_eq!(
+            packed,
+            vec![
+                ((0.0, 0.0), 1.0),
+                ((0.2, 0.2), 5.0),
+                ((0.4, 0.4), 1.0),
+                ((2.0, 3.0), 3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_reencodes_late_delta_window() {
+        let samples = vec![(0.0, 1.0), (0.3, 4.0), (0.6, 2.0), (2.0, 8.0), (2.2, 9.0)];
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackDeltaStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.0,
+        };
+
+        let mut full = TimeSeriesDataPacker::new();
+        let expected = full.pack(samples.clone(), attrs.clone()).unwrap();
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer
+            .pack(vec![samples[0], samples[2], samples[3]], attrs)
+            .unwrap();
+        let packed = packer.insert(vec![samples[4], samples[1]]).unwrap();
+
+        assert_eq!(expected, packed);
+    }
+
+    #[test]
+    fn test_insert_merges_equal_timestamp_as_late_sample() {
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackDeltaStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.0,
+        };
+
+        let mut full = TimeSeriesDataPacker::new();
+        let expected = full
+            .pack(vec![(0.0, 1.0), (1.5, 2.0), (1.5, 5.0)], attrs.clone())
+            .unwrap()
+            .to_vec();
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer.pack(vec![(0.0, 1.0), (1.5, 2.0)], attrs).unwrap();
+
+        assert_eq!(packer.insert(vec![(1.5, 5.0)]), Ok(expected.as_slice()));
+        assert_eq!(packer.decode(), full.decode());
+    }
+
+    #[test]
+    fn test_insert_lateness_watermark_policies() {
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackRunLengthStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.0,
+        };
+
+        let mut rejecting =
+            TimeSeriesDataPacker::new().with_lateness(1_000_000, TSPackLatePolicy::Reject);
+        rejecting
+            .pack(vec![(0.0, 1.0), (5.0, 1.0)], attrs.clone())
+            .unwrap();
+        assert_eq!(
+            rejecting.insert(vec![(3.0, 2.0)]),
+            Err(TSPackError::LateSampleRejected {
+                timestamp: 3.0,
+                watermark: 4.0,
+            })
+        );
+
+        let mut buffering =
+            TimeSeriesDataPacker::new().with_lateness(1_000_000, TSPackLatePolicy::SideBuffer);
+        let packed = buffering
+            .pack(vec![(0.0, 1.0), (5.0, 1.0)], attrs)
+            .unwrap()
+            .to_vec();
+        assert_eq!(buffering.insert(vec![(3.0, 2.0)]), Ok(packed.as_slice()));
+        assert_eq!(buffering.take_late_samples(), vec![(3.0, 2.0)]);
+        assert!(buffering.take_late_samples().is_empty());
+    }
+
+    #[test]
+    fn test_decode_reconstructs_every_window() {
+        let samples = vec![(0.0, 1.5), (0.5, 2.5), (2.0, -1.0), (2.5, 4.0)];
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackDeltaStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.0,
+        };
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer.pack(samples.clone(), attrs).unwrap();
+
+        assert_eq!(packer.decode(), samples);
+        assert_ne!(packer.unpack().1, samples);
+    }
+
+    #[test]
+    fn test_size_stats_follow_appended_and_late_samples() {
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackSimilarValuesStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.001,
+        };
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer
+            .pack(vec![(0.0, 1.0), (0.2, 1.0), (0.4, 1.0)], attrs)
+            .unwrap();
+        packer.append(vec![(0.6, 1.0), (2.0, 5.0)]).unwrap();
+        packer.insert(vec![(1.5, 5.0)]).unwrap();
+
+        let stats = packer.size_stats();
+        assert_eq!(stats.samples, 6);
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.raw_bytes(), 96);
+        assert_eq!(stats.encoded_bytes(), 48);
+        assert_eq!(stats.ratio(), 2.0);
+        assert_eq!(stats.bits_per_sample(), 64.0);
+    }
+
+    #[test]
+    fn test_pack_slice_borrows_sorted_input() {
+        let attrs = TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackSimilarValuesStrategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.001,
+        };
+        let sorted = vec![(0.0, 1.0), (0.5, 1.0), (2.0, 3.0), (2.5, 3.0)];
+        let unsorted = vec![(2.5, 3.0), (0.0, 1.0), (2.0, 3.0), (0.5, 1.0)];
+
+        let mut owned = TimeSeriesDataPacker::new();
+        let expected = owned.pack(sorted.clone(), attrs.clone()).unwrap().to_vec();
+
+        let mut borrowed = TimeSeriesDataPacker::new();
+        assert_eq!(
+            borrowed.pack_slice(&sorted, attrs.clone()).unwrap(),
+            expected.as_slice()
+        );
+        assert_eq!(
+            borrowed.pack_slice(&unsorted, attrs).unwrap(),
+            expected.as_slice()
+        );
+        assert_eq!(borrowed.decode(), owned.decode());
+        assert_eq!(borrowed.into_packed_samples(), expected);
+    }
+
+    #[test]
+    fn test_pack_columns_matches_rows_for_every_strategy() {
+        let micros: Vec<i64> = vec![0, 250_000, 500_000, 1_750_000, 2_000_000, 2_250_000];
+        let values = vec![1.0, 1.0, 1.5, 4.0, 4.25, 4.0];
+        let seconds: Vec<f64> = micros.iter().map(|us| *us as f64 / 1_000_000.0).collect();
+        let rows: Vec<TSSamples> = seconds
+            .iter()
+            .copied()
+            .zip(values.iter().copied())
+            .collect();
+
+        let strategies = vec![
+            TSPackStrategyType::TSPackSimilarValuesStrategy,
+            TSPackStrategyType::TSPackMeanStrategy {
+                values_compression_percent: 10,
+            },
+            TSPackStrategyType::TSPackXorStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+            TSPackStrategyType::TSPackDeltaOfDeltaStrategy,
+            TSPackStrategyType::TSPackRunLengthStrategy,
+            TSPackStrategyType::TSPackSimple8bStrategy,
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates: vec![
+                    TSPackStrategyType::TSPackRunLengthStrategy,
+                    TSPackStrategyType::TSPackXorStrategy,
+                ],
+                max_error: 0.0,
+            },
+        ];
+
+        for strategy in strategies {
+            let attrs = TSPackAttributes {
+                strategy_types: vec![strategy],
+                microseconds_time_window: 1_000_000,
+                precision_epsilon: 0.01,
+            };
+
+            let mut packer = TimeSeriesDataPacker::new();
+            let expected = TSPackedColumns::from(packer.pack(rows.clone(), attrs.clone()).unwrap());
+
+            let mut columnar = TimeSeriesDataPacker::new();
+            assert_eq!(
+                columnar.pack_columns(&seconds[..], &values, attrs.clone()),
+                Ok(expected.clone())
+            );
+            assert_eq!(
+                columnar.pack_columns(&micros[..], &values, attrs.clone()),
+                Ok(expected.clone())
+            );
+
+            let reversed_micros: Vec<i64> = micros.iter().rev().copied().collect();
+            let reversed_values: Vec<f64> = values.iter().rev().copied().collect();
+            assert_eq!(
+                columnar.pack_columns(&reversed_micros[..], &reversed_values, attrs),
+                Ok(expected)
+            );
+            assert_eq!(columnar.decode(), packer.decode());
+        }
+
+        let mut packer = TimeSeriesDataPacker::new();
+        assert_eq!(
+            packer.pack_columns(
+                &seconds[..],
+                &values[1..],
+                TSPackAttributes::builder().build().unwrap()
+            ),
+            Err(TSPackError::ColumnLengthMismatch {
+                timestamps: 6,
+                values: 5,
+            })
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_windows_match_sequential_bit_for_bit() {
+        let samples: Vec<TSSamples> = (0..5_000)
+            .map(|i| {
+                let ts = i as f64 * 0.01;
+                (ts, (ts * 0.7).sin().round() * 3.0 + (i % 7) as f64 * 0.001)
+            })
+            .collect();
+
+        let strategies = vec![
+            TSPackStrategyType::TSPackSimilarValuesStrategy,
+            TSPackStrategyType::TSPackMeanStrategy {
+                values_compression_percent: 5,
+            },
+            TSPackStrategyType::TSPackXorStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+            TSPackStrategyType::TSPackDeltaOfDeltaStrategy,
+            TSPackStrategyType::TSPackRunLengthStrategy,
+            TSPackStrategyType::TSPackSimple8bStrategy,
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates: strategies::auto::default_auto_candidates(),
+                max_error: 0.01,
+            },
+        ];
+
+        for strategy in strategies {
+            let attrs = TSPackAttributes {
+                strategy_types: vec![strategy],
+                microseconds_time_window: 250_000,
+                precision_epsilon: 0.01,
+            };
+
+            let mut sequential = TimeSeriesDataPacker::new();
+            let mut parallel = TimeSeriesDataPacker::new().with_parallel_windows(true);
+
+            let (expected, expected_stats) = sequential
+                .pack_with_stats(samples.clone(), attrs.clone())
+                .unwrap();
+            let (packed, stats) = parallel.pack_with_stats(samples.clone(), attrs).unwrap();
+
+            let bits = |packed: &[TSPackedSamples]| -> Vec<[u64; 3]> {
+                packed
+                    .iter()
+                    .map(|((start, end), value)| [start.to_bits(), end.to_bits(), value.to_bits()])
+                    .collect()
+            };
+            assert_eq!(bits(&packed), bits(&expected));
+            assert!(stats.windows > 100);
+            assert_eq!(stats.windows, expected_stats.windows);
+            assert_eq!(stats.window_entries, expected_stats.window_entries);
+            assert_eq!(stats.merges, expected_stats.merges);
+            assert_eq!(parallel.decode(), sequential.decode());
+        }
+    }
 }
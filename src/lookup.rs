@@ -0,0 +1,285 @@
+use crate::decode::decode_window_iter;
+use crate::helpers::round_to_precision;
+use crate::helpers::uses_bit_exact_encoding;
+use crate::helpers::TIMESTAMP_TOLERANCE;
+use crate::TSPackedSamples;
+use crate::TimeSeriesDataPacker;
+
+/// How [`TimeSeriesDataPacker::value_at`] fills the time between two entries.
+/// Inside a packed range every mode returns the range's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TSPackInterpolation {
+    /// Last entry at or before `t` (step-hold); `None` before the first one.
+    #[default]
+    Previous,
+    /// First entry at or after `t`; `None` after the last one.
+    Next,
+    /// Closer of the previous and the next entry, the previous one on a tie.
+    Nearest,
+    /// Straight line from the end of the previous entry to the start of the
+    /// next one; `None` outside the series.
+    Linear,
+}
+
+/// Value at `ts` between the `previous` and the `next` entry, `None` in a gap.
+pub(crate) fn interpolate(
+    previous: Option<TSPackedSamples>,
+    next: Option<TSPackedSamples>,
+    ts: f64,
+    mode: TSPackInterpolation,
+) -> Option<f64> {
+    let value = match mode {
+        TSPackInterpolation::Previous => previous?.1,
+        TSPackInterpolation::Next => next?.1,
+        TSPackInterpolation::Nearest => match (previous, next) {
+            (Some(((_, previous_end), previous_value)), Some(((next_start, _), next_value))) => {
+                if ts - previous_end <= next_start - ts {
+                    previous_value
+                } else {
+                    next_value
+                }
+            }
+            (Some((_, value)), None) | (None, Some((_, value))) => value,
+            (None, None) => return None,
+        },
+        TSPackInterpolation::Linear => {
+            let ((_, t0), v0) = previous?;
+            let ((t1, _), v1) = next?;
+
+            // same range, or points closer than the timestamp tolerance
+            if t1 - t0 <= TIMESTAMP_TOLERANCE {
+                v0
+            } else {
+                v0 + (v1 - v0) * ((ts - t0) / (t1 - t0)).clamp(0.0, 1.0)
+            }
+        }
+    };
+
+    // NaN marks a gap in the series
+    (!value.is_nan()).then_some(value)
+}
+
+/// Entries at or before and at or after `ts` within `entries` (sorted, non
+/// overlapping). Both are the same range when `ts` falls inside it.
+fn neighbours(
+    entries: &[TSPackedSamples],
+    ts: f64,
+) -> (Option<TSPackedSamples>, Option<TSPackedSamples>) {
+    let index = entries.partition_point(|((start, _), _)| *start <= ts + TIMESTAMP_TOLERANCE);
+    let previous = index.checked_sub(1).map(|i| entries[i]);
+
+    let next = match previous {
+        Some(entry @ ((_, end), _)) if end >= ts - TIMESTAMP_TOLERANCE => Some(entry),
+        _ => entries.get(index).copied(),
+    };
+
+    (previous, next)
+}
+
+impl TimeSeriesDataPacker {
+    /// Value of the packed series at `ts` (seconds), see [`TSPackInterpolation`].
+    ///
+    /// Range chains binary-search the packed ranges; bit-exact chains decode
+    /// only the window holding `ts` (and the first sample of the next one when
+    /// needed). A NaN sample, storable with XOR Gorilla and Run-length chains,
+    /// marks a gap: lookups landing on it return `None` and linear
+    /// interpolation does not bridge it.
+    pub fn value_at(&self, ts: f64, mode: TSPackInterpolation) -> Option<f64> {
+        self.lookup(ts, mode, &mut None)
+    }
+
+    /// [`Self::value_at`] for many timestamps. Consecutive timestamps in the
+    /// same window reuse its decoded samples, so sorted input is cheapest.
+    pub fn values_at(&self, timestamps: &[f64], mode: TSPackInterpolation) -> Vec<Option<f64>> {
+        let mut decoded_window = None;
+
+        timestamps
+            .iter()
+            .map(|&ts| self.lookup(ts, mode, &mut decoded_window))
+            .collect()
+    }
+
+    fn lookup(
+        &self,
+        ts: f64,
+        mode: TSPackInterpolation,
+        decoded_window: &mut Option<(usize, Vec<TSPackedSamples>)>,
+    ) -> Option<f64> {
+        let attributes = self.attributes.as_ref()?;
+        if ts.is_nan() {
+            return None;
+        }
+
+        if !uses_bit_exact_encoding(&attributes.strategy_types) {
+            let round = |entry: Option<TSPackedSamples>| {
+                entry.map(|(range, value)| {
+                    (
+                        range,
+                        round_to_precision(value, attributes.precision_epsilon),
+                    )
+                })
+            };
+            let (previous, next) = neighbours(&self.packed_samples, ts);
+            return interpolate(round(previous), round(next), ts, mode);
+        }
+
+        let decode = |window: usize| {
+            let from = self.window_starts[window].1;
+            let to = self
+                .window_starts
+                .get(window + 1)
+                .map(|(_, offset)| *offset)
+                .unwrap_or(self.packed_samples.len());
+
+            decode_window_iter(
+                &self.packed_samples[from..to],
+                &attributes.strategy_types,
+                attributes.precision_epsilon,
+            )
+            .map(|(sample_ts, value)| ((sample_ts, sample_ts), value))
+        };
+
+        let following = self
+            .window_starts
+            .partition_point(|(window_start, _)| *window_start <= ts + TIMESTAMP_TOLERANCE);
+
+        let Some(window) = following.checked_sub(1) else {
+            // before the first window
+            let first = (!self.window_starts.is_empty())
+                .then(|| decode(0).next())
+                .flatten();
+            return interpolate(None, first, ts, mode);
+        };
+
+        if decoded_window.as_ref().map(|(cached, _)| *cached) != Some(window) {
+            *decoded_window = Some((window, decode(window).collect()));
+        }
+        let (_, samples) = decoded_window.as_ref()?;
+
+        let (previous, mut next) = neighbours(samples, ts);
+        if next.is_none() && following < self.window_starts.len() {
+            next = decode(following).next();
+        }
+
+        interpolate(previous, next, ts, mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::auto::default_auto_candidates;
+    use crate::test_support::pack;
+    use crate::{TSPackStrategyType, TSSamples};
+
+    #[test]
+    fn ranges_hold_their_value_in_every_mode() {
+        // ((0, 2), 1.0), ((3, 3.2), 5.0)
+        let samples = vec![(0.0, 1.0), (1.0, 1.0), (2.0, 1.0), (3.0, 5.0), (3.2, 5.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackSimilarValuesStrategy);
+
+        for mode in [
+            TSPackInterpolation::Previous,
+            TSPackInterpolation::Next,
+            TSPackInterpolation::Nearest,
+            TSPackInterpolation::Linear,
+        ] {
+            assert_eq!(packer.value_at(1.5, mode), Some(1.0), "{mode:?}");
+            assert_eq!(packer.value_at(3.1, mode), Some(5.0), "{mode:?}");
+        }
+
+        assert_eq!(
+            packer.values_at(&[2.25, 2.75], TSPackInterpolation::Previous),
+            vec![Some(1.0), Some(1.0)]
+        );
+        assert_eq!(
+            packer.values_at(&[2.25, 2.75], TSPackInterpolation::Next),
+            vec![Some(5.0), Some(5.0)]
+        );
+        assert_eq!(
+            packer.values_at(&[2.25, 2.75], TSPackInterpolation::Nearest),
+            vec![Some(1.0), Some(5.0)]
+        );
+        assert_eq!(
+            packer.values_at(&[2.25, 2.5], TSPackInterpolation::Linear),
+            vec![Some(2.0), Some(3.0)]
+        );
+    }
+
+    #[test]
+    fn outside_the_series() {
+        let samples = vec![(1.0, 1.0), (2.0, 3.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackXorStrategy);
+
+        assert_eq!(packer.value_at(0.5, TSPackInterpolation::Previous), None);
+        assert_eq!(packer.value_at(0.5, TSPackInterpolation::Next), Some(1.0));
+        assert_eq!(
+            packer.value_at(0.5, TSPackInterpolation::Nearest),
+            Some(1.0)
+        );
+        assert_eq!(packer.value_at(0.5, TSPackInterpolation::Linear), None);
+        assert_eq!(
+            packer.value_at(9.0, TSPackInterpolation::Previous),
+            Some(3.0)
+        );
+        assert_eq!(packer.value_at(9.0, TSPackInterpolation::Next), None);
+        assert_eq!(
+            packer.value_at(f64::NAN, TSPackInterpolation::Previous),
+            None
+        );
+        assert_eq!(
+            TimeSeriesDataPacker::new().value_at(1.0, TSPackInterpolation::Nearest),
+            None
+        );
+    }
+
+    #[test]
+    fn bit_exact_lookups_cross_window_edges() {
+        let samples: Vec<TSSamples> = (0..40).map(|i| (i as f64 * 0.3, i as f64)).collect();
+
+        let strategies = vec![
+            TSPackStrategyType::TSPackXorStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+            TSPackStrategyType::TSPackDeltaOfDeltaStrategy,
+            TSPackStrategyType::TSPackSimple8bStrategy,
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates: default_auto_candidates(),
+                max_error: 0.0,
+            },
+        ];
+
+        for strategy in strategies {
+            let packer = pack(samples.clone(), strategy.clone());
+
+            // windows start at 0.0, 1.2, 2.4, ... so 1.05 sits between windows
+            let timestamps = [0.9, 1.05, 1.2, 5.0];
+            let linear = packer.values_at(&timestamps, TSPackInterpolation::Linear);
+            let expected = [3.0, 3.5, 4.0, 5.0 / 0.3];
+
+            for (value, expected) in linear.iter().zip(expected) {
+                assert!((value.unwrap() - expected).abs() < 1e-4, "{strategy:?}");
+            }
+            assert_eq!(
+                packer.value_at(1.05, TSPackInterpolation::Next),
+                Some(4.0),
+                "{strategy:?}"
+            );
+            assert_eq!(
+                packer.value_at(1.15, TSPackInterpolation::Nearest),
+                Some(4.0),
+                "{strategy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn nan_samples_mark_gaps() {
+        let samples = vec![(0.0, 1.0), (1.0, f64::NAN), (2.0, 3.0), (3.0, 4.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackXorStrategy);
+
+        assert_eq!(packer.value_at(1.5, TSPackInterpolation::Previous), None);
+        assert_eq!(packer.value_at(0.5, TSPackInterpolation::Linear), None);
+        assert_eq!(packer.value_at(1.5, TSPackInterpolation::Next), Some(3.0));
+        assert_eq!(packer.value_at(2.5, TSPackInterpolation::Linear), Some(3.5));
+    }
+}
@@ -0,0 +1,285 @@
+use crate::helpers::step_hold_value;
+use crate::helpers::TIMESTAMP_TOLERANCE;
+use crate::TSPackedSamples;
+use crate::TSSamples;
+
+/// How a reconstructed series is evaluated at the original timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TSPackAlignment {
+    /// Hold the last reconstructed value (ranges keep their value until the next one).
+    #[default]
+    StepHold,
+    /// Interpolate linearly between the surrounding reconstructed points.
+    Linear,
+}
+
+/// Reconstruction error of a decoded series against the original samples.
+///
+/// `snr_db` and `psnr_db` are infinite for an exact reconstruction, including
+/// of a constant or all-zero series. Otherwise `snr_db` is negative infinity
+/// for an all-zero original and `psnr_db` for a constant one. `nrmse` is the
+/// RMSE divided by the original value range (0 for a constant series).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TSPackErrorMetrics {
+    pub count: usize,
+    pub mae: f64,
+    pub mse: f64,
+    pub rmse: f64,
+    pub max_error: f64,
+    pub snr_db: f64,
+    pub psnr_db: f64,
+    pub nrmse: f64,
+}
+
+/// Value of `reconstructed` (sorted by timestamp) at `ts`. Timestamps before
+/// the first point take the first value; `None` for an empty reconstruction.
+pub fn value_at(reconstructed: &[TSSamples], ts: f64, alignment: TSPackAlignment) -> Option<f64> {
+    let first = reconstructed.first()?;
+
+    let Some(held) = step_hold_value(reconstructed, ts) else {
+        return Some(first.1);
+    };
+
+    match alignment {
+        TSPackAlignment::StepHold => Some(held),
+        TSPackAlignment::Linear => {
+            let index = reconstructed.partition_point(|(rts, _)| *rts <= ts + TIMESTAMP_TOLERANCE);
+            let (t0, v0) = reconstructed[index - 1];
+
+            match reconstructed.get(index) {
+                Some(&(t1, v1)) if (ts - t0).abs() > TIMESTAMP_TOLERANCE && t1 > t0 => {
+                    Some(v0 + (v1 - v0) * (ts - t0) / (t1 - t0))
+                }
+                _ => Some(v0),
+            }
+        }
+    }
+}
+
+/// Computes MAE, MSE, RMSE, max error, SNR/PSNR and NRMSE of `reconstructed`
+/// against `original`, aligning by timestamp so strategies that return fewer
+/// points than the input (ranges) are measured correctly.
+pub fn compute_error_metrics(
+    original: &[TSSamples],
+    reconstructed: &[TSSamples],
+    alignment: TSPackAlignment,
+) -> TSPackErrorMetrics {
+    if original.is_empty() {
+        return TSPackErrorMetrics::default();
+    }
+
+    let mut sum_abs = 0.0;
+    let mut sum_sq = 0.0;
+    let mut max_error: f64 = 0.0;
+    let mut signal_power = 0.0;
+    let mut min_value = f64::INFINITY;
+    let mut max_value = f64::NEG_INFINITY;
+
+    for &(ts, value) in original {
+        let error = match value_at(reconstructed, ts, alignment) {
+            Some(aligned) if aligned.to_bits() == value.to_bits() => 0.0,
+            Some(aligned) => (aligned - value).abs(),
+            None => f64::INFINITY,
+        };
+
+        sum_abs += error;
+        sum_sq += error * error;
+        max_error = max_error.max(error);
+        signal_power += value * value;
+        min_value = min_value.min(value);
+        max_value = max_value.max(value);
+    }
+
+    let n = original.len() as f64;
+    let mse = sum_sq / n;
+    let rmse = mse.sqrt();
+    let range = max_value - min_value;
+
+    // 0/0 would be NaN: an exact reconstruction is infinitely good, an inexact
+    // one of a zero signal or a zero range infinitely bad
+    let (snr_db, psnr_db) = if mse == 0.0 {
+        (f64::INFINITY, f64::INFINITY)
+    } else {
+        let snr_db = if signal_power == 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            10.0 * ((signal_power / n) / mse).log10()
+        };
+        let psnr_db = if range == 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            20.0 * (range / rmse).log10()
+        };
+        (snr_db, psnr_db)
+    };
+
+    TSPackErrorMetrics {
+        count: original.len(),
+        mae: sum_abs / n,
+        mse,
+        rmse,
+        max_error,
+        snr_db,
+        psnr_db,
+        nrmse: if range > 0.0 { rmse / range } else { 0.0 },
+    }
+}
+
+/// Storage size of packed output against the raw samples it encodes.
+///
+/// A raw sample is 16 bytes (`f64` timestamp and value), a packed entry 24
+/// bytes (`f64` start, end and value). Every packed entry counts, including
+/// window headers and tagged Simple-8b words, so ratios are comparable across
+/// strategies.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TSPackSizeStats {
+    pub samples: usize,
+    pub entries: usize,
+    pub raw_timestamp_bytes: usize,
+    pub raw_value_bytes: usize,
+    pub encoded_timestamp_bytes: usize,
+    pub encoded_value_bytes: usize,
+}
+
+fn ratio(raw: usize, encoded: usize) -> f64 {
+    if encoded == 0 {
+        0.0
+    } else {
+        raw as f64 / encoded as f64
+    }
+}
+
+impl TSPackSizeStats {
+    pub fn new(samples: usize, packed: &[TSPackedSamples]) -> Self {
+        Self {
+            samples,
+            entries: packed.len(),
+            raw_timestamp_bytes: samples * 8,
+            raw_value_bytes: samples * 8,
+            encoded_timestamp_bytes: packed.len() * 16,
+            encoded_value_bytes: packed.len() * 8,
+        }
+    }
+
+    pub fn raw_bytes(&self) -> usize {
+        self.raw_timestamp_bytes + self.raw_value_bytes
+    }
+
+    pub fn encoded_bytes(&self) -> usize {
+        self.encoded_timestamp_bytes + self.encoded_value_bytes
+    }
+
+    /// Encoded bits spent per raw sample (128 for uncompressed data).
+    pub fn bits_per_sample(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            (self.encoded_bytes() * 8) as f64 / self.samples as f64
+        }
+    }
+
+    /// Raw bytes / encoded bytes; below 1.0 means the output is larger.
+    pub fn ratio(&self) -> f64 {
+        ratio(self.raw_bytes(), self.encoded_bytes())
+    }
+
+    pub fn timestamp_ratio(&self) -> f64 {
+        ratio(self.raw_timestamp_bytes, self.encoded_timestamp_bytes)
+    }
+
+    pub fn value_ratio(&self) -> f64 {
+        ratio(self.raw_value_bytes, self.encoded_value_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_reconstruction() {
+        let samples = vec![(0.0, 1.0), (1.0, 2.0), (2.0, 3.0)];
+        let metrics = compute_error_metrics(&samples, &samples, TSPackAlignment::StepHold);
+
+        assert_eq!(metrics.count, 3);
+        assert_eq!(metrics.mae, 0.0);
+        assert_eq!(metrics.max_error, 0.0);
+        assert_eq!(metrics.nrmse, 0.0);
+        assert!(metrics.snr_db.is_infinite());
+    }
+
+    #[test]
+    fn aligns_ranges_by_timestamp() {
+        let original = vec![(0.0, 1.0), (0.1, 1.0), (0.2, 1.0), (0.3, 2.0), (0.4, 2.0)];
+        // what unpack returns for ((0.0, 0.2), 1.0), ((0.3, 0.4), 2.0)
+        let reconstructed = vec![(0.0, 1.0), (0.2, 1.0), (0.3, 2.0), (0.4, 2.0)];
+
+        let metrics = compute_error_metrics(&original, &reconstructed, TSPackAlignment::StepHold);
+        assert_eq!(metrics.max_error, 0.0);
+
+        // zipping by index would compare (0.1, 1.0) with (0.2, 1.0) and (0.2, 1.0) with (0.3, 2.0)
+        let metrics = compute_error_metrics(&original, &reconstructed, TSPackAlignment::Linear);
+        assert_eq!(metrics.max_error, 0.0);
+    }
+
+    #[test]
+    fn linear_and_step_hold_differ_between_points() {
+        let original = vec![(0.0, 0.0), (0.5, 1.0), (1.0, 2.0)];
+        let reconstructed = vec![(0.0, 0.0), (1.0, 2.0)];
+
+        let step = compute_error_metrics(&original, &reconstructed, TSPackAlignment::StepHold);
+        let linear = compute_error_metrics(&original, &reconstructed, TSPackAlignment::Linear);
+
+        assert_eq!(step.max_error, 1.0);
+        assert!((step.mae - 1.0 / 3.0).abs() < 1e-12);
+        assert!((step.mse - 1.0 / 3.0).abs() < 1e-12);
+        assert!((step.nrmse - step.rmse / 2.0).abs() < 1e-12);
+        assert_eq!(linear.max_error, 0.0);
+    }
+
+    #[test]
+    fn snr_and_psnr() {
+        let original = vec![(0.0, 10.0), (1.0, -10.0)];
+        let reconstructed = vec![(0.0, 9.0), (1.0, -9.0)];
+        let metrics = compute_error_metrics(&original, &reconstructed, TSPackAlignment::StepHold);
+
+        assert!((metrics.snr_db - 20.0).abs() < 1e-9);
+        assert!((metrics.psnr_db - 20.0 * 20.0_f64.log10()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn snr_and_psnr_of_constant_and_zero_series() {
+        let constant = vec![(0.0, 5.0), (1.0, 5.0)];
+        let metrics = compute_error_metrics(&constant, &constant, TSPackAlignment::StepHold);
+        assert_eq!(metrics.snr_db, f64::INFINITY);
+        assert_eq!(metrics.psnr_db, f64::INFINITY);
+
+        let zeros = vec![(0.0, 0.0), (1.0, 0.0)];
+        let metrics = compute_error_metrics(&zeros, &zeros, TSPackAlignment::StepHold);
+        assert_eq!(metrics.snr_db, f64::INFINITY);
+        assert_eq!(metrics.psnr_db, f64::INFINITY);
+
+        let off = vec![(0.0, 1.0), (1.0, 1.0)];
+        let metrics = compute_error_metrics(&zeros, &off, TSPackAlignment::StepHold);
+        assert_eq!(metrics.snr_db, f64::NEG_INFINITY);
+        assert_eq!(metrics.psnr_db, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn size_stats_count_bytes_not_entries() {
+        // 6 samples packed into 4 entries is fewer entries but more bytes
+        let packed = vec![((0.0, 0.0), 1.0); 4];
+        let stats = TSPackSizeStats::new(6, &packed);
+
+        assert_eq!(stats.raw_bytes(), 96);
+        assert_eq!(stats.encoded_bytes(), 96);
+        assert_eq!(stats.ratio(), 1.0);
+        assert_eq!(stats.timestamp_ratio(), 0.75);
+        assert_eq!(stats.value_ratio(), 1.5);
+        assert_eq!(stats.bits_per_sample(), 128.0);
+
+        let empty = TSPackSizeStats::new(0, &[]);
+        assert_eq!(empty.ratio(), 0.0);
+        assert_eq!(empty.bits_per_sample(), 0.0);
+    }
+}
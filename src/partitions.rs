@@ -0,0 +1,460 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use crate::validation::validate_samples;
+use crate::TSPackAttributes;
+use crate::TSPackError;
+use crate::TSPackedSamples;
+use crate::TSSamples;
+use crate::TimeSeriesDataPacker;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Largest UTC offset in use (UTC+14), in seconds.
+const MAX_UTC_OFFSET_SECONDS: i32 = 14 * 3_600;
+
+/// Calendar interval covered by one [`TSPackBucket`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TSPackBucketGranularity {
+    Minute,
+    Hour,
+    Day,
+    /// ISO week, starting on Monday.
+    Week,
+    Month,
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's
+/// `days_from_civil`).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// `(year, month)` of the day `days` since 1970-01-01.
+fn year_month_from_days(days: i64) -> (i64, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month)
+}
+
+/// One calendar interval `[start, end)` of a [`TSPackPartitions`], packed
+/// on its own with its own attributes.
+#[derive(Debug, Clone)]
+pub struct TSPackBucket {
+    start: f64,
+    end: f64,
+    attributes: TSPackAttributes,
+    packer: TimeSeriesDataPacker,
+}
+
+impl TSPackBucket {
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// First timestamp of the next bucket (exclusive end).
+    pub fn end(&self) -> f64 {
+        self.end
+    }
+
+    pub fn attributes(&self) -> &TSPackAttributes {
+        &self.attributes
+    }
+
+    pub fn packer(&self) -> &TimeSeriesDataPacker {
+        &self.packer
+    }
+}
+
+/// Packed series split into calendar-aligned buckets (minute, hour, day, ISO
+/// week or month) in a fixed UTC offset, so reading a known short time range
+/// touches only the buckets that overlap it.
+///
+/// Timestamps are seconds since the Unix epoch. New buckets are packed with
+/// the container's attributes; [`Self::repack_bucket`] gives a bucket its own.
+#[derive(Debug, Clone)]
+pub struct TSPackPartitions {
+    granularity: TSPackBucketGranularity,
+    utc_offset_seconds: i32,
+    attributes: TSPackAttributes,
+    // sorted by start, never overlapping
+    buckets: Vec<TSPackBucket>,
+}
+
+impl TSPackPartitions {
+    /// `utc_offset_seconds` shifts bucket boundaries to local time, e.g.
+    /// `3_600` for UTC+1 so days start at local midnight. It is fixed, no
+    /// daylight saving time is applied.
+    pub fn new(
+        granularity: TSPackBucketGranularity,
+        utc_offset_seconds: i32,
+        attributes: TSPackAttributes,
+    ) -> Result<Self, TSPackError> {
+        if utc_offset_seconds.abs() > MAX_UTC_OFFSET_SECONDS {
+            return Err(TSPackError::InvalidUtcOffset(utc_offset_seconds));
+        }
+        attributes.validate()?;
+
+        Ok(Self {
+            granularity,
+            utc_offset_seconds,
+            attributes,
+            buckets: Vec::new(),
+        })
+    }
+
+    pub fn granularity(&self) -> TSPackBucketGranularity {
+        self.granularity
+    }
+
+    pub fn utc_offset_seconds(&self) -> i32 {
+        self.utc_offset_seconds
+    }
+
+    pub fn buckets(&self) -> &[TSPackBucket] {
+        &self.buckets
+    }
+
+    /// Bucket holding `ts`, if it has any data.
+    pub fn bucket(&self, ts: f64) -> Option<&TSPackBucket> {
+        self.bucket_index(ts).ok().map(|index| &self.buckets[index])
+    }
+
+    /// Start of the bucket `ts` falls into.
+    pub fn bucket_start(&self, ts: f64) -> f64 {
+        let offset = f64::from(self.utc_offset_seconds);
+        let local = ts + offset;
+
+        let local_start = match self.granularity {
+            TSPackBucketGranularity::Minute => (local / 60.0).floor() * 60.0,
+            TSPackBucketGranularity::Hour => (local / 3_600.0).floor() * 3_600.0,
+            TSPackBucketGranularity::Day => (local / SECONDS_PER_DAY).floor() * SECONDS_PER_DAY,
+            TSPackBucketGranularity::Week => {
+                let days = (local / SECONDS_PER_DAY).floor() as i64;
+                // 1970-01-01 was a Thursday
+                let monday = days - (days + 3).rem_euclid(7);
+                monday as f64 * SECONDS_PER_DAY
+            }
+            TSPackBucketGranularity::Month => {
+                let (year, month) = year_month_from_days((local / SECONDS_PER_DAY).floor() as i64);
+                days_from_civil(year, month, 1) as f64 * SECONDS_PER_DAY
+            }
+        };
+
+        local_start - offset
+    }
+
+    /// Start of the bucket after the one starting at `start`.
+    fn next_bucket_start(&self, start: f64) -> f64 {
+        match self.granularity {
+            TSPackBucketGranularity::Minute => start + 60.0,
+            TSPackBucketGranularity::Hour => start + 3_600.0,
+            TSPackBucketGranularity::Day => start + SECONDS_PER_DAY,
+            TSPackBucketGranularity::Week => start + 7.0 * SECONDS_PER_DAY,
+            TSPackBucketGranularity::Month => {
+                let offset = f64::from(self.utc_offset_seconds);
+                let days = ((start + offset) / SECONDS_PER_DAY).floor() as i64;
+                let (year, month) = year_month_from_days(days);
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                days_from_civil(year, month, 1) as f64 * SECONDS_PER_DAY - offset
+            }
+        }
+    }
+
+    fn bucket_index(&self, ts: f64) -> Result<usize, usize> {
+        let index = self.buckets.partition_point(|bucket| bucket.end <= ts);
+        match self.buckets.get(index) {
+            Some(bucket) if bucket.start <= ts => Ok(index),
+            _ => Err(index),
+        }
+    }
+
+    /// Adds samples in any order. Each one goes to the bucket of its
+    /// timestamp: existing buckets merge them like
+    /// [`TimeSeriesDataPacker::insert`], missing ones are created and packed
+    /// with the container's attributes. Samples are validated against the
+    /// attributes of their bucket before any bucket changes.
+    pub fn insert(&mut self, samples: Vec<TSSamples>) -> Result<(), TSPackError> {
+        if let Some(index) = samples.iter().position(|(ts, _)| !ts.is_finite()) {
+            return Err(TSPackError::NonFiniteTimestamp { index });
+        }
+
+        // caller positions of the sorted samples, for validation errors
+        let mut order: Vec<usize> = (0..samples.len()).collect();
+        order.sort_by(|&a, &b| {
+            samples[a]
+                .0
+                .partial_cmp(&samples[b].0)
+                .unwrap_or(Ordering::Equal)
+        });
+        let samples: Vec<TSSamples> = order.iter().map(|&index| samples[index]).collect();
+
+        // (bucket start, bucket end, samples of that bucket)
+        let mut chunks: Vec<(f64, f64, Range<usize>)> = Vec::new();
+        let mut from = 0;
+
+        while from < samples.len() {
+            let start = self.bucket_start(samples[from].0);
+            let end = self.next_bucket_start(start);
+            let to = from + samples[from..].partition_point(|(ts, _)| *ts < end);
+
+            let attributes = match self.bucket_index(start) {
+                Ok(index) => &self.buckets[index].attributes,
+                Err(_) => &self.attributes,
+            };
+            validate_samples(&samples[from..to], attributes).map_err(|error| match error {
+                TSPackError::NonFiniteValue { index } => TSPackError::NonFiniteValue {
+                    index: order[from + index],
+                },
+                error => error,
+            })?;
+
+            chunks.push((start, end, from..to));
+            from = to;
+        }
+
+        for (start, end, range) in chunks {
+            let chunk = samples[range].to_vec();
+
+            match self.bucket_index(start) {
+                Ok(index) => {
+                    self.buckets[index].packer.insert(chunk)?;
+                }
+                Err(index) => {
+                    let mut packer = TimeSeriesDataPacker::new();
+                    packer.pack(chunk, self.attributes.clone())?;
+
+                    self.buckets.insert(
+                        index,
+                        TSPackBucket {
+                            start,
+                            end,
+                            attributes: self.attributes.clone(),
+                            packer,
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Re-encodes the decoded samples of the bucket holding `ts` with
+    /// `attributes`, e.g. a coarser chain for old data. Returns `false` when
+    /// that bucket has no data.
+    pub fn repack_bucket(
+        &mut self,
+        ts: f64,
+        attributes: TSPackAttributes,
+    ) -> Result<bool, TSPackError> {
+        let Ok(index) = self.bucket_index(ts) else {
+            return Ok(false);
+        };
+
+        let bucket = &mut self.buckets[index];
+        let mut packer = TimeSeriesDataPacker::new();
+        packer.pack(bucket.packer.decode(), attributes.clone())?;
+
+        bucket.packer = packer;
+        bucket.attributes = attributes;
+        Ok(true)
+    }
+
+    /// [`TimeSeriesDataPacker::query_range`] over the buckets overlapping
+    /// `[start, end]`, found by binary search; other buckets are not read.
+    pub fn query_range(&self, start: f64, end: f64) -> Vec<TSPackedSamples> {
+        let first = self.buckets.partition_point(|bucket| bucket.end <= start);
+
+        self.buckets[first..]
+            .iter()
+            .take_while(|bucket| bucket.start <= end)
+            .flat_map(|bucket| bucket.packer.query_range(start, end))
+            .collect()
+    }
+
+    /// Decoded samples of all buckets, in time order.
+    pub fn decode(&self) -> Vec<TSSamples> {
+        self.buckets
+            .iter()
+            .flat_map(|bucket| bucket.packer.iter_decode())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TSPackStrategyType;
+
+    // 2024-02-28T23:30:00Z, a Wednesday
+    const FEB_28_2024: f64 = 1_709_163_000.0;
+
+    fn attributes(strategy: TSPackStrategyType) -> TSPackAttributes {
+        TSPackAttributes {
+            strategy_types: vec![strategy],
+            microseconds_time_window: 60_000_000,
+            precision_epsilon: 0.001,
+        }
+    }
+
+    fn partitions(granularity: TSPackBucketGranularity, utc_offset: i32) -> TSPackPartitions {
+        TSPackPartitions::new(
+            granularity,
+            utc_offset,
+            attributes(TSPackStrategyType::TSPackXorStrategy),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn calendar_alignment() {
+        let day = partitions(TSPackBucketGranularity::Day, 0);
+        assert_eq!(day.bucket_start(FEB_28_2024), FEB_28_2024 - 23.5 * 3_600.0);
+
+        // UTC+1: already 2024-02-29 00:30 local time
+        let local_day = partitions(TSPackBucketGranularity::Day, 3_600);
+        assert_eq!(local_day.bucket_start(FEB_28_2024), FEB_28_2024 - 1_800.0);
+
+        let week = partitions(TSPackBucketGranularity::Week, 0);
+        assert_eq!(
+            week.bucket_start(FEB_28_2024),
+            days_from_civil(2024, 2, 26) as f64 * SECONDS_PER_DAY
+        );
+
+        let month = partitions(TSPackBucketGranularity::Month, 0);
+        let february = month.bucket_start(FEB_28_2024);
+        assert_eq!(
+            february,
+            days_from_civil(2024, 2, 1) as f64 * SECONDS_PER_DAY
+        );
+        // leap year: February has 29 days
+        assert_eq!(
+            month.next_bucket_start(february) - february,
+            29.0 * SECONDS_PER_DAY
+        );
+        assert_eq!(
+            month.next_bucket_start(days_from_civil(2023, 12, 1) as f64 * SECONDS_PER_DAY),
+            days_from_civil(2024, 1, 1) as f64 * SECONDS_PER_DAY
+        );
+
+        let minute = partitions(TSPackBucketGranularity::Minute, 0);
+        assert_eq!(minute.bucket_start(FEB_28_2024 + 59.5), FEB_28_2024);
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(year_month_from_days(-1), (1969, 12));
+    }
+
+    #[test]
+    fn inserts_route_samples_to_their_buckets() {
+        let mut hourly = partitions(TSPackBucketGranularity::Hour, 0);
+        let samples: Vec<TSSamples> = (0..120)
+            .map(|i| (FEB_28_2024 + i as f64 * 60.0, i as f64))
+            .collect();
+
+        // late half first, then the early half into existing and new buckets
+        hourly.insert(samples[60..].to_vec()).unwrap();
+        hourly.insert(samples[..60].to_vec()).unwrap();
+
+        let starts: Vec<f64> = hourly.buckets().iter().map(|b| b.start()).collect();
+        assert_eq!(
+            starts,
+            vec![
+                FEB_28_2024 - 1_800.0,
+                FEB_28_2024 + 1_800.0,
+                FEB_28_2024 + 5_400.0
+            ]
+        );
+        assert_eq!(hourly.decode(), samples);
+        assert!(hourly.bucket(FEB_28_2024 + 1_800.0).is_some());
+        assert!(hourly.bucket(FEB_28_2024 + 9_000.0).is_none());
+    }
+
+    #[test]
+    fn queries_read_overlapping_buckets_only() {
+        let mut daily = partitions(TSPackBucketGranularity::Day, 0);
+        let samples: Vec<TSSamples> = (0..96)
+            .map(|i| (FEB_28_2024 + i as f64 * 3_600.0, (i % 5) as f64))
+            .collect();
+        daily.insert(samples.clone()).unwrap();
+        assert_eq!(daily.buckets().len(), 5);
+
+        let (from, to) = (FEB_28_2024 + 20.0 * 3_600.0, FEB_28_2024 + 30.0 * 3_600.0);
+        let queried: Vec<TSSamples> = daily
+            .query_range(from, to)
+            .into_iter()
+            .map(|((ts, _), value)| (ts, value))
+            .collect();
+        let expected: Vec<TSSamples> = samples
+            .into_iter()
+            .filter(|(ts, _)| (from..=to).contains(ts))
+            .collect();
+
+        assert_eq!(queried, expected);
+    }
+
+    #[test]
+    fn buckets_keep_their_own_attributes() {
+        let mut daily = partitions(TSPackBucketGranularity::Day, 0);
+        let samples: Vec<TSSamples> = (0..48)
+            .map(|i| (FEB_28_2024 + i as f64 * 3_600.0, 20.0))
+            .collect();
+        daily.insert(samples).unwrap();
+
+        let similar = attributes(TSPackStrategyType::TSPackSimilarValuesStrategy);
+        assert!(daily.repack_bucket(FEB_28_2024, similar.clone()).unwrap());
+        assert!(!daily.repack_bucket(0.0, similar.clone()).unwrap());
+
+        assert_eq!(daily.buckets()[0].attributes(), &similar);
+        assert_eq!(daily.buckets()[0].packer().packed_samples().len(), 1);
+        assert_eq!(
+            daily.buckets()[1].attributes(),
+            &attributes(TSPackStrategyType::TSPackXorStrategy)
+        );
+
+        // NaN values are fine for XOR buckets, not for the Similar Values one
+        assert_eq!(
+            daily.insert(vec![(FEB_28_2024 + 600.0, f64::NAN)]),
+            Err(TSPackError::NonFiniteValue { index: 0 })
+        );
+        assert_eq!(
+            daily.insert(vec![
+                (FEB_28_2024 + 1_200.0, 20.0),
+                (FEB_28_2024 + 900.0, 20.0),
+                (FEB_28_2024 + 600.0, f64::NAN)
+            ]),
+            Err(TSPackError::NonFiniteValue { index: 2 })
+        );
+        assert_eq!(
+            daily.insert(vec![
+                (FEB_28_2024 + 48.0 * 3_600.0, f64::NAN),
+                (f64::NAN, 0.0)
+            ]),
+            Err(TSPackError::NonFiniteTimestamp { index: 1 })
+        );
+        assert_eq!(
+            TSPackPartitions::new(TSPackBucketGranularity::Day, 20 * 3_600, similar).err(),
+            Some(TSPackError::InvalidUtcOffset(72_000))
+        );
+    }
+}
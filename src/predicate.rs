@@ -0,0 +1,193 @@
+use crate::blocks::TSPackBlockContainer;
+use crate::blocks::TSPackBlockIndexEntry;
+use crate::TSSamples;
+
+/// Condition on sample values for the predicate queries of
+/// [`TSPackBlockContainer`]. NaN samples never match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TSPackPredicate {
+    GreaterThan(f64),
+    GreaterOrEqual(f64),
+    LessThan(f64),
+    LessOrEqual(f64),
+    /// `low <= value <= high`.
+    Between(f64, f64),
+}
+
+impl TSPackPredicate {
+    pub fn matches(&self, value: f64) -> bool {
+        match *self {
+            TSPackPredicate::GreaterThan(limit) => value > limit,
+            TSPackPredicate::GreaterOrEqual(limit) => value >= limit,
+            TSPackPredicate::LessThan(limit) => value < limit,
+            TSPackPredicate::LessOrEqual(limit) => value <= limit,
+            TSPackPredicate::Between(low, high) => value >= low && value <= high,
+        }
+    }
+
+    /// Whether a block with this zone map can hold a matching sample.
+    pub fn may_match(&self, block: &TSPackBlockIndexEntry) -> bool {
+        // NaN bounds: the block holds no comparable sample
+        match *self {
+            TSPackPredicate::GreaterThan(_) | TSPackPredicate::GreaterOrEqual(_) => {
+                self.matches(block.max)
+            }
+            TSPackPredicate::LessThan(_) | TSPackPredicate::LessOrEqual(_) => {
+                self.matches(block.min)
+            }
+            TSPackPredicate::Between(low, high) => block.max >= low && block.min <= high,
+        }
+    }
+
+    /// Whether a block with this zone map surely holds a matching sample:
+    /// its `min` and `max` are sample values themselves.
+    fn surely_matches(&self, block: &TSPackBlockIndexEntry) -> bool {
+        self.matches(block.min) || self.matches(block.max)
+    }
+}
+
+impl TSPackBlockContainer {
+    /// Blocks overlapping `[start, end]` whose zone map does not rule out
+    /// `predicate`; everything else is skipped without decoding.
+    pub fn candidate_blocks(&self, start: f64, end: f64, predicate: TSPackPredicate) -> Vec<usize> {
+        self.seek(start, end)
+            .filter(|&block| predicate.may_match(&self.index()[block]))
+            .collect()
+    }
+
+    /// Decoded samples inside `[start, end]` matching `predicate`. Only the
+    /// [`Self::candidate_blocks`] are decoded, so the result is exact.
+    pub fn select(&self, start: f64, end: f64, predicate: TSPackPredicate) -> Vec<TSSamples> {
+        self.candidate_blocks(start, end, predicate)
+            .into_iter()
+            .flat_map(|block| self.decode_block(block).unwrap_or_default())
+            .filter(|&(ts, value)| ts >= start && ts <= end && predicate.matches(value))
+            .collect()
+    }
+
+    /// Whether any sample inside `[start, end]` matches `predicate`, e.g.
+    /// "did pressure ever exceed 120 kPa last month?". A block fully inside
+    /// the range whose `min` or `max` matches answers from its zone map
+    /// alone; every other candidate block is decoded.
+    pub fn any(&self, start: f64, end: f64, predicate: TSPackPredicate) -> bool {
+        self.candidate_blocks(start, end, predicate)
+            .into_iter()
+            .any(|block| {
+                let zone = &self.index()[block];
+                if zone.start >= start && zone.end <= end && predicate.surely_matches(zone) {
+                    return true;
+                }
+
+                self.decode_block(block)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .any(|(ts, value)| ts >= start && ts <= end && predicate.matches(value))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::auto::default_auto_candidates;
+    use crate::test_support::pack;
+    use crate::TSPackStrategyType;
+    use crate::TimeSeriesDataPacker;
+
+    fn container(strategy: TSPackStrategyType) -> (TimeSeriesDataPacker, TSPackBlockContainer) {
+        // a pressure spike to 130.0 at 4.5 s, 101.0 .. 104.0 elsewhere
+        let samples: Vec<TSSamples> = (0..100)
+            .map(|i| {
+                let value = if i == 45 {
+                    130.0
+                } else {
+                    101.0 + (i % 4) as f64
+                };
+                (i as f64 * 0.1, value)
+            })
+            .collect();
+
+        let packer = pack(samples, strategy);
+        let container = packer.block_container().unwrap();
+        (packer, container)
+    }
+
+    #[test]
+    fn zone_maps_summarize_every_block() {
+        let (packer, container) = container(TSPackStrategyType::TSPackDeltaStrategy);
+
+        for (block, zone) in container.index().iter().enumerate() {
+            let samples = container.decode_block(block).unwrap();
+            let sum: f64 = samples.iter().map(|(_, v)| v).sum();
+
+            assert!((zone.sum - sum).abs() < 1e-9);
+            assert_eq!(zone.first, samples[0].1);
+            assert_eq!(zone.last, samples[samples.len() - 1].1);
+        }
+
+        let total: f64 = container.index().iter().map(|zone| zone.sum).sum();
+        let decoded: f64 = packer.decode().iter().map(|(_, v)| v).sum();
+        assert!((total - decoded).abs() < 1e-9);
+    }
+
+    #[test]
+    fn predicates_skip_blocks_that_cannot_match() {
+        let strategies = vec![
+            TSPackStrategyType::TSPackXorStrategy,
+            TSPackStrategyType::TSPackSimple8bStrategy,
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates: default_auto_candidates(),
+                max_error: 0.0,
+            },
+            TSPackStrategyType::TSPackRunLengthStrategy,
+        ];
+
+        for strategy in strategies {
+            let (packer, container) = container(strategy.clone());
+            let spike = TSPackPredicate::GreaterThan(120.0);
+
+            // windows start at 0.0, 1.1, 2.2, 3.3, 4.4, ...
+            assert_eq!(container.candidate_blocks(0.0, 10.0, spike), vec![4]);
+            assert_eq!(container.select(0.0, 10.0, spike), vec![(4.5, 130.0)]);
+            assert!(container.any(0.0, 10.0, spike), "{strategy:?}");
+            assert!(!container.any(4.6, 10.0, spike), "{strategy:?}");
+
+            let band = TSPackPredicate::Between(102.0, 103.0);
+            let expected: Vec<TSSamples> = packer
+                .decode()
+                .into_iter()
+                .filter(|(ts, v)| (2.0..=6.0).contains(ts) && (102.0..=103.0).contains(v))
+                .collect();
+            assert_eq!(container.select(2.0, 6.0, band), expected, "{strategy:?}");
+        }
+    }
+
+    #[test]
+    fn nan_samples_never_match() {
+        let container = pack(
+            vec![(0.0, f64::NAN), (0.5, f64::NAN), (2.0, 1.0)],
+            TSPackStrategyType::TSPackXorStrategy,
+        )
+        .block_container()
+        .unwrap();
+
+        assert!(container.index()[0].min.is_nan());
+        assert_eq!(container.index()[0].sum, 0.0);
+        assert_eq!(
+            container.candidate_blocks(0.0, 3.0, TSPackPredicate::LessThan(5.0)),
+            vec![1]
+        );
+        assert!(!TSPackPredicate::Between(0.0, 1.0).matches(f64::NAN));
+    }
+
+    #[test]
+    fn band_inside_the_zone_map_decodes_the_block() {
+        let (_packer, container) = container(TSPackStrategyType::TSPackXorStrategy);
+        let zone = container.index()[0];
+        assert_eq!((zone.min, zone.max), (101.0, 104.0));
+
+        // strictly between min and max: the zone map cannot decide
+        assert!(container.any(0.0, 10.0, TSPackPredicate::Between(101.5, 102.5)));
+        assert!(!container.any(0.0, 10.0, TSPackPredicate::Between(102.2, 102.8)));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use crate::validation::validate_attributes;
+use crate::TSPackAttributes;
+use crate::TSPackError;
+use crate::TSPackPrecisionDataType;
+use crate::TSPackStrategyType;
+
+impl TSPackPrecisionDataType {
+    pub const ALL: [TSPackPrecisionDataType; 5] = [
+        TSPackPrecisionDataType::MilisValues,
+        TSPackPrecisionDataType::WavDerivedAudio,
+        TSPackPrecisionDataType::IoTSensors,
+        TSPackPrecisionDataType::HighPrecisionTelemetry,
+        TSPackPrecisionDataType::ScientificData,
+    ];
+
+    /// Name of the built-in preset in [`TSPackPresets`], if this data type
+    /// has one.
+    pub fn preset_name(&self) -> Option<&'static str> {
+        match self {
+            TSPackPrecisionDataType::MilisValues => Some("milis-values"),
+            TSPackPrecisionDataType::WavDerivedAudio => Some("wav-derived-audio"),
+            TSPackPrecisionDataType::IoTSensors => Some("iot-sensors"),
+            TSPackPrecisionDataType::HighPrecisionTelemetry => None,
+            TSPackPrecisionDataType::ScientificData => Some("scientific-data"),
+        }
+    }
+
+    /// Ready-made window, strategy chain and epsilon for this kind of data,
+    /// picked from the `strategy_showcase` demo results:
+    ///
+    /// - `MilisValues`: mean ±5% over 1 s windows (noisy pressure, 16x fewer entries)
+    /// - `WavDerivedAudio`: Simple-8b over 1 s windows (PCM excerpt, 7.4x, within epsilon)
+    /// - `IoTSensors`: similar values over 2 s windows (temperature plateaus, 5.9x)
+    /// - `HighPrecisionTelemetry`: none. No chain compresses the RPM ramp
+    ///   within 1e-7 (every one stays below 1x by [`crate::TSPackSizeStats`]).
+    /// - `ScientificData`: Simple-8b over 1 s windows (CNC vibration spectrum,
+    ///   1.1x, within epsilon)
+    pub fn recommended_attributes(&self) -> Option<TSPackAttributes> {
+        let (strategy_types, microseconds_time_window) = match self {
+            TSPackPrecisionDataType::MilisValues => (
+                vec![TSPackStrategyType::TSPackMeanStrategy {
+                    values_compression_percent: 5,
+                }],
+                1_000_000,
+            ),
+            TSPackPrecisionDataType::WavDerivedAudio => {
+                (vec![TSPackStrategyType::TSPackSimple8bStrategy], 1_000_000)
+            }
+            TSPackPrecisionDataType::IoTSensors => (
+                vec![TSPackStrategyType::TSPackSimilarValuesStrategy],
+                2_000_000,
+            ),
+            TSPackPrecisionDataType::HighPrecisionTelemetry => return None,
+            TSPackPrecisionDataType::ScientificData => {
+                (vec![TSPackStrategyType::TSPackSimple8bStrategy], 1_000_000)
+            }
+        };
+
+        Some(TSPackAttributes {
+            strategy_types,
+            microseconds_time_window,
+            precision_epsilon: self.epsilon(),
+        })
+    }
+}
+
+/// Named pipeline presets: the built-in one for every [`TSPackPrecisionDataType`]
+/// with recommended attributes, plus any registered by the user.
+#[derive(Debug, Clone)]
+pub struct TSPackPresets {
+    presets: BTreeMap<String, TSPackAttributes>,
+}
+
+impl Default for TSPackPresets {
+    fn default() -> Self {
+        let presets = TSPackPrecisionDataType::ALL
+            .iter()
+            .filter_map(|data_type| {
+                Some((
+                    data_type.preset_name()?.to_string(),
+                    data_type.recommended_attributes()?,
+                ))
+            })
+            .collect();
+
+        Self { presets }
+    }
+}
+
+impl TSPackPresets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers (or replaces) a named preset after validating it. Returns the
+    /// preset previously stored under that name.
+    pub fn register(
+        &mut self,
+        name: &str,
+        attributes: TSPackAttributes,
+    ) -> Result<Option<TSPackAttributes>, TSPackError> {
+        validate_attributes(&attributes)?;
+        Ok(self.presets.insert(name.to_string(), attributes))
+    }
+
+    /// Registers a preset from a pipeline spec such as `window=1s;eps=1e-3;similar`.
+    pub fn register_spec(
+        &mut self,
+        name: &str,
+        spec: &str,
+    ) -> Result<Option<TSPackAttributes>, TSPackError> {
+        self.register(name, spec.parse()?)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TSPackAttributes> {
+        self.presets.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recommended_attributes_are_valid() {
+        for data_type in TSPackPrecisionDataType::ALL {
+            let Some(attributes) = data_type.recommended_attributes() else {
+                continue;
+            };
+            assert_eq!(attributes.validate(), Ok(()));
+            assert_eq!(attributes.precision_epsilon, data_type.epsilon());
+        }
+
+        assert_eq!(
+            TSPackPrecisionDataType::IoTSensors
+                .recommended_attributes()
+                .unwrap()
+                .to_string(),
+            "window=2s;eps=0.00001;similar"
+        );
+        assert_eq!(
+            TSPackPrecisionDataType::HighPrecisionTelemetry.recommended_attributes(),
+            None
+        );
+    }
+
+    #[test]
+    fn scientific_preset_compresses_the_spectrum() {
+        let samples: Vec<(f64, f64)> = include_str!("../examples/data/cnc_vibration_spectrum.csv")
+            .lines()
+            .skip(1)
+            .map(|line| {
+                let (ts, value) = line.split_once(',').unwrap();
+                (ts.parse::<f64>().unwrap() / 1e6, value.parse().unwrap())
+            })
+            .collect();
+
+        let attributes = TSPackPrecisionDataType::ScientificData
+            .recommended_attributes()
+            .unwrap();
+        let mut packer = crate::TimeSeriesDataPacker::new();
+        packer.pack(samples, attributes).unwrap();
+
+        assert!(packer.size_stats().ratio() > 1.0);
+    }
+
+    #[test]
+    fn built_in_and_registered_presets() {
+        let mut presets = TSPackPresets::new();
+
+        assert_eq!(
+            presets.get("scientific-data"),
+            TSPackPrecisionDataType::ScientificData
+                .recommended_attributes()
+                .as_ref()
+        );
+        assert_eq!(presets.names().count(), 4);
+        assert!(presets.get("high-precision-telemetry").is_none());
+
+        assert_eq!(
+            presets.register_spec("valve", "window=2s;eps=0;rle"),
+            Ok(None)
+        );
+        assert_eq!(
+            presets.get("valve").unwrap().strategy_types,
+            vec![TSPackStrategyType::TSPackRunLengthStrategy]
+        );
+
+        assert_eq!(
+            presets.register_spec("broken", "window=0;similar"),
+            Err(TSPackError::InvalidWindow)
+        );
+        assert!(presets.get("broken").is_none());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::decode::decode_window_iter;
+use crate::helpers::round_to_precision;
+use crate::helpers::uses_bit_exact_encoding;
+use crate::TSPackedSamples;
+use crate::TimeSeriesDataPacker;
+
+impl TimeSeriesDataPacker {
+    /// Entries of the packed series that overlap `[start, end]` (seconds,
+    /// inclusive), without unpacking the rest of the series.
+    ///
+    /// Range chains binary-search the packed ranges and clip the first and
+    /// last one to the query. Bit-exact chains binary-search the window
+    /// anchors and decode only the overlapping windows; their samples come
+    /// back as zero-length ranges `((ts, ts), value)` with the original values.
+    /// A query costs O(log n + k) for k returned entries. Empty when
+    /// `start > end` or either bound is NaN.
+    pub fn query_range(&self, start: f64, end: f64) -> Vec<TSPackedSamples> {
+        let Some(attributes) = &self.attributes else {
+            return Vec::new();
+        };
+
+        if start.is_nan() || end.is_nan() || start > end {
+            return Vec::new();
+        }
+
+        if !uses_bit_exact_encoding(&attributes.strategy_types) {
+            let first = self
+                .packed_samples
+                .partition_point(|((_, range_end), _)| *range_end < start);
+
+            return self.packed_samples[first..]
+                .iter()
+                .take_while(|((range_start, _), _)| *range_start <= end)
+                .map(|&((range_start, range_end), value)| {
+                    (
+                        (range_start.max(start), range_end.min(end)),
+                        round_to_precision(value, attributes.precision_epsilon),
+                    )
+                })
+                .collect();
+        }
+
+        let windows = &self.window_starts;
+        let first = windows
+            .partition_point(|(window_start, _)| *window_start <= start)
+            .saturating_sub(1);
+        let last = windows.partition_point(|(window_start, _)| *window_start <= end);
+
+        let mut result = Vec::new();
+
+        for window in first..last {
+            let from = windows[window].1;
+            let to = windows
+                .get(window + 1)
+                .map(|(_, offset)| *offset)
+                .unwrap_or(self.packed_samples.len());
+
+            result.extend(
+                decode_window_iter(
+                    &self.packed_samples[from..to],
+                    &attributes.strategy_types,
+                    attributes.precision_epsilon,
+                )
+                .skip_while(|(ts, _)| *ts < start)
+                .take_while(|(ts, _)| *ts <= end)
+                .map(|(ts, value)| ((ts, ts), value)),
+            );
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::strategies::auto::default_auto_candidates;
+    use crate::test_support::pack;
+    use crate::{TSPackStrategyType, TSSamples, TimeSeriesDataPacker};
+
+    fn samples() -> Vec<TSSamples> {
+        (0..200)
+            .map(|i| {
+                (
+                    i as f64 * 0.1,
+                    ((i / 10) % 4) as f64 + (i % 2) as f64 * 0.25,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bit_exact_chains_decode_only_the_queried_samples() {
+        let strategies = vec![
+            TSPackStrategyType::TSPackXorStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+            TSPackStrategyType::TSPackDeltaOfDeltaStrategy,
+            TSPackStrategyType::TSPackSimple8bStrategy,
+            TSPackStrategyType::TSPackAutoStrategy {
+                candidates: default_auto_candidates(),
+                max_error: 0.0,
+            },
+        ];
+
+        for strategy in strategies {
+            let packer = pack(samples(), strategy.clone());
+
+            let expected: Vec<TSSamples> = packer
+                .decode()
+                .into_iter()
+                .filter(|(ts, _)| (4.25..=7.0).contains(ts))
+                .collect();
+            let queried: Vec<TSSamples> = packer
+                .query_range(4.25, 7.0)
+                .into_iter()
+                .map(|((start, end), value)| {
+                    assert_eq!(start, end);
+                    (start, value)
+                })
+                .collect();
+
+            assert_eq!(queried, expected, "{strategy:?}");
+        }
+    }
+
+    #[test]
+    fn range_chains_clip_edge_ranges() {
+        let samples = vec![(0.0, 1.0), (2.0, 1.0), (3.0, 5.0), (6.0, 5.0), (7.0, 2.0)];
+
+        let packer = pack(samples, TSPackStrategyType::TSPackRunLengthStrategy);
+
+        assert_eq!(
+            packer.query_range(1.0, 4.5),
+            vec![((1.0, 2.0), 1.0), ((3.0, 4.5), 5.0)]
+        );
+        assert_eq!(packer.query_range(6.5, 100.0), vec![((7.0, 7.0), 2.0)]);
+        assert_eq!(packer.query_range(2.5, 2.75), vec![]);
+    }
+
+    #[test]
+    fn empty_or_inverted_ranges_return_nothing() {
+        assert_eq!(TimeSeriesDataPacker::new().query_range(0.0, 1.0), vec![]);
+
+        let packer = pack(samples(), TSPackStrategyType::TSPackSimilarValuesStrategy);
+        assert_eq!(packer.query_range(5.0, 1.0), vec![]);
+        assert_eq!(packer.query_range(f64::NAN, 1.0), vec![]);
+        assert!(!packer.query_range(1.0, 1.0).is_empty());
+    }
+}
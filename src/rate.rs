@@ -0,0 +1,245 @@
+use crate::TSPackCounterStrategy;
+use crate::TSPackStrategyType;
+use crate::TSSamples;
+use crate::TimeSeriesDataPacker;
+
+/// Reset-aware step between two counter readings: a reading below the
+/// previous one means the counter restarted from zero, so the whole new
+/// reading is the increase.
+fn counter_increase(previous: f64, current: f64) -> f64 {
+    if current < previous {
+        current
+    } else {
+        current - previous
+    }
+}
+
+impl TimeSeriesDataPacker {
+    /// Samples inside `[start, end]` (range chains contribute the start and
+    /// end point of every range), skipping NaN gaps.
+    fn counter_samples(&self, start: f64, end: f64) -> impl Iterator<Item = TSSamples> + '_ {
+        let valid = !(start.is_nan() || end.is_nan() || start > end);
+
+        self.entries_from(start)
+            .take_while(move |((range_start, _), _)| valid && *range_start <= end)
+            .flat_map(|((range_start, range_end), value)| {
+                let end_point = (range_end != range_start).then_some((range_end, value));
+                std::iter::once((range_start, value)).chain(end_point)
+            })
+            .filter(move |(ts, value)| *ts >= start && *ts <= end && !value.is_nan())
+    }
+
+    /// Increase of a monotonic counter over `[start, end]` (seconds,
+    /// inclusive), from the first to the last sample inside the range.
+    ///
+    /// A sample below the previous one is a counter reset: the counter is
+    /// taken to restart from zero, so the reset adds the new reading instead
+    /// of a large negative step. Only the windows overlapping the range are
+    /// decoded. `None` with fewer than two samples in the range.
+    pub fn increase(&self, start: f64, end: f64) -> Option<f64> {
+        let mut samples = self.counter_samples(start, end);
+        let (_, mut previous) = samples.next()?;
+
+        let mut increase = None;
+        for (_, value) in samples {
+            *increase.get_or_insert(0.0) += counter_increase(previous, value);
+            previous = value;
+        }
+
+        increase
+    }
+
+    /// Per-second average rate of a monotonic counter over `[start, end]`:
+    /// [`Self::increase`] divided by the time between the first and the last
+    /// sample inside the range. `None` when they share a timestamp.
+    pub fn rate(&self, start: f64, end: f64) -> Option<f64> {
+        let mut samples = self.counter_samples(start, end);
+        let (first_ts, mut previous) = samples.next()?;
+
+        let mut increase = 0.0;
+        let mut last_ts = first_ts;
+        for (ts, value) in samples {
+            increase += counter_increase(previous, value);
+            previous = value;
+            last_ts = ts;
+        }
+
+        (last_ts > first_ts).then(|| increase / (last_ts - first_ts))
+    }
+
+    /// Per-second rate between consecutive samples inside `[start, end]`,
+    /// stamped with the later sample. Counter resets are handled like in
+    /// [`Self::increase`]; samples sharing a timestamp are skipped.
+    pub fn derivative(&self, start: f64, end: f64) -> Vec<TSSamples> {
+        let mut derivative = Vec::new();
+        let mut previous: Option<TSSamples> = None;
+
+        for (ts, value) in self.counter_samples(start, end) {
+            if let Some((previous_ts, previous_value)) = previous {
+                if ts <= previous_ts {
+                    continue;
+                }
+                derivative.push((
+                    ts,
+                    counter_increase(previous_value, value) / (ts - previous_ts),
+                ));
+            }
+            previous = Some((ts, value));
+        }
+
+        derivative
+    }
+
+    /// Timestamps inside `[start, end]` where the counter reset, i.e. the
+    /// first sample below its predecessor.
+    ///
+    /// Counter chains read the reset markers recorded at pack time, plus the
+    /// drops between windows; other chains compare the decoded samples.
+    pub fn counter_resets(&self, start: f64, end: f64) -> Vec<f64> {
+        let counter_chain = self.attributes.as_ref().is_some_and(|attributes| {
+            attributes.strategy_types.last() == Some(&TSPackStrategyType::TSPackCounterStrategy)
+        });
+        if counter_chain {
+            return self.recorded_counter_resets(start, end);
+        }
+
+        let mut resets = Vec::new();
+        let mut previous: Option<f64> = None;
+
+        for (ts, value) in self.counter_samples(start, end) {
+            if previous.is_some_and(|previous| value < previous) {
+                resets.push(ts);
+            }
+            previous = Some(value);
+        }
+
+        resets
+    }
+
+    /// [`Self::counter_resets`] of a Counter chain, decoding only the window
+    /// before the range to know the value its first window starts from.
+    fn recorded_counter_resets(&self, start: f64, end: f64) -> Vec<f64> {
+        if start.is_nan() || end.is_nan() || start > end {
+            return Vec::new();
+        }
+
+        let windows = &self.window_starts;
+        let window_entries = |window: usize| {
+            let from = windows[window].1;
+            let to = windows
+                .get(window + 1)
+                .map_or(self.packed_samples.len(), |(_, offset)| *offset);
+            &self.packed_samples[from..to]
+        };
+
+        let first = windows
+            .partition_point(|(window_start, _)| *window_start <= start)
+            .saturating_sub(1);
+        let mut previous = first
+            .checked_sub(1)
+            .and_then(|window| TSPackCounterStrategy::iter_unpack(window_entries(window)).last())
+            .map(|(_, value)| value);
+
+        let mut resets = Vec::new();
+        let overlapping = (first..windows.len()).take_while(|&window| windows[window].0 <= end);
+        for window in overlapping {
+            let entries = window_entries(window);
+            resets.extend(
+                TSPackCounterStrategy::resets(entries, previous)
+                    .into_iter()
+                    .map(|(ts, _)| ts)
+                    .filter(|ts| *ts >= start && *ts <= end),
+            );
+            previous = TSPackCounterStrategy::iter_unpack(entries)
+                .last()
+                .map(|(_, value)| value);
+        }
+
+        resets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::pack;
+
+    // energy meter: +2 kWh per second, reset to 0 after 5 s, then +3 per second
+    fn meter() -> Vec<TSSamples> {
+        (0..10)
+            .map(|i| {
+                let value = if i < 5 {
+                    100.0 + 2.0 * i as f64
+                } else {
+                    3.0 * (i - 5) as f64
+                };
+                (i as f64, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resets_do_not_become_negative_steps() {
+        for strategy in [
+            TSPackStrategyType::TSPackCounterStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+            TSPackStrategyType::TSPackXorStrategy,
+        ] {
+            let packer = pack(meter(), strategy.clone());
+
+            // 4 steps of 2, the reset to 0 adds 0, then 4 steps of 3
+            assert_eq!(packer.increase(0.0, 9.0), Some(20.0), "{strategy:?}");
+            assert_eq!(packer.rate(0.0, 9.0), Some(20.0 / 9.0), "{strategy:?}");
+            assert_eq!(packer.counter_resets(0.0, 9.0), vec![5.0], "{strategy:?}");
+
+            let derivative = packer.derivative(3.0, 7.0);
+            assert_eq!(
+                derivative,
+                vec![(4.0, 2.0), (5.0, 0.0), (6.0, 3.0), (7.0, 3.0)],
+                "{strategy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn ranges_count_their_extent() {
+        // the counter stays at 10.0 from 0 s to 4 s, then jumps to 16.0
+        let samples = vec![(0.0, 10.0), (2.0, 10.0), (4.0, 10.0), (6.0, 16.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackRunLengthStrategy);
+
+        assert_eq!(packer.increase(0.0, 6.0), Some(6.0));
+        assert_eq!(packer.rate(0.0, 6.0), Some(1.0));
+        assert_eq!(packer.derivative(0.0, 6.0), vec![(4.0, 0.0), (6.0, 3.0)]);
+    }
+
+    #[test]
+    fn resets_between_windows() {
+        // the drop to 1.0 opens the second 1 s window
+        let samples = vec![(0.0, 10.0), (0.5, 12.0), (1.2, 1.0), (1.4, 3.0)];
+
+        for strategy in [
+            TSPackStrategyType::TSPackCounterStrategy,
+            TSPackStrategyType::TSPackDeltaStrategy,
+        ] {
+            let packer = pack(samples.clone(), strategy.clone());
+
+            assert_eq!(packer.counter_resets(0.0, 2.0), vec![1.2], "{strategy:?}");
+            assert_eq!(packer.increase(0.0, 2.0), Some(5.0), "{strategy:?}");
+        }
+
+        let packer = pack(samples, TSPackStrategyType::TSPackCounterStrategy);
+        assert_eq!(packer.counter_resets(1.0, 2.0), vec![1.2]);
+        assert_eq!(packer.counter_resets(1.3, 2.0), vec![]);
+    }
+
+    #[test]
+    fn too_few_samples() {
+        let packer = pack(meter(), TSPackStrategyType::TSPackCounterStrategy);
+
+        assert_eq!(packer.increase(2.5, 3.5), None);
+        assert_eq!(packer.rate(3.0, 3.0), None);
+        assert_eq!(packer.increase(9.0, 0.0), None);
+        assert_eq!(packer.derivative(9.0, 20.0), vec![]);
+        assert_eq!(TimeSeriesDataPacker::new().increase(0.0, 1.0), None);
+    }
+}
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+
+use crate::TimeSeriesDataPacker;
+
+/// Time spent in one state of a [`TSPackStateStats`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSPackStateDuration {
+    pub value: f64,
+    /// Seconds the series held this value.
+    pub duration: f64,
+    /// Number of separate runs of this value.
+    pub runs: usize,
+}
+
+/// One uninterrupted run of a state, clipped to the queried range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSPackStateRun {
+    pub value: f64,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl TSPackStateRun {
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+/// Time-in-state statistics of a digital or enumerated series over
+/// `[start, end]`, see [`TimeSeriesDataPacker::state_stats`].
+///
+/// `states` is sorted by value. `transition_matrix[from][to]` counts the
+/// switches between `states[from]` and `states[to]`; `transitions` is their
+/// total. `longest_run` is the first of the longest runs.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TSPackStateStats {
+    pub start: f64,
+    pub end: f64,
+    pub states: Vec<TSPackStateDuration>,
+    pub transitions: usize,
+    pub transition_matrix: Vec<Vec<usize>>,
+    pub longest_run: Option<TSPackStateRun>,
+}
+
+impl TSPackStateStats {
+    /// Total duration of `value`, 0 when the series never held it.
+    pub fn duration_of(&self, value: f64) -> f64 {
+        self.states
+            .iter()
+            .find(|state| state.value == value)
+            .map_or(0.0, |state| state.duration)
+    }
+}
+
+impl TimeSeriesDataPacker {
+    /// Per-state durations, transition counts, transition matrix and the
+    /// longest run over `[start, end]` (seconds, inclusive), e.g. how long a
+    /// valve was open and how many times it switched.
+    ///
+    /// Every distinct value is a state and holds until the next entry starts
+    /// (step-hold, as in [`Self::aggregate`]). Run-length, Similar Values and
+    /// Mean chains are read straight from their runs; bit-exact chains decode
+    /// only the overlapping windows. A NaN sample is a gap: it belongs to no
+    /// state and the value after it does not count as a transition.
+    pub fn state_stats(&self, start: f64, end: f64) -> TSPackStateStats {
+        let mut stats = TSPackStateStats {
+            start,
+            end,
+            ..TSPackStateStats::default()
+        };
+
+        if start.is_nan() || end.is_nan() || start > end {
+            return stats;
+        }
+
+        let mut runs: Vec<TSPackStateRun> = Vec::new();
+        let mut switches: Vec<(f64, f64)> = Vec::new();
+        // the run being extended, `None` after a gap
+        let mut current: Option<TSPackStateRun> = None;
+
+        let mut entries = self.entries_held_from(start).peekable();
+
+        while let Some(((range_start, range_end), value)) = entries.next() {
+            if range_start > end {
+                break;
+            }
+
+            if value.is_nan() {
+                runs.extend(current.take());
+                continue;
+            }
+
+            let from = range_start.max(start);
+            let held_until = entries
+                .peek()
+                .map(|((next, _), _)| *next)
+                .unwrap_or(range_end)
+                .min(end);
+
+            match current.as_mut() {
+                Some(run) if run.value == value => run.end = held_until,
+                _ => {
+                    if let Some(run) = current.take() {
+                        switches.push((run.value, value));
+                        runs.push(run);
+                    }
+                    current = Some(TSPackStateRun {
+                        value,
+                        start: from,
+                        end: held_until,
+                    });
+                }
+            }
+        }
+        runs.extend(current);
+
+        let mut states: Vec<TSPackStateDuration> = Vec::new();
+        for run in &runs {
+            match states.iter_mut().find(|state| state.value == run.value) {
+                Some(state) => {
+                    state.duration += run.duration();
+                    state.runs += 1;
+                }
+                None => states.push(TSPackStateDuration {
+                    value: run.value,
+                    duration: run.duration(),
+                    runs: 1,
+                }),
+            }
+        }
+        states.sort_by(|a, b| a.value.partial_cmp(&b.value).unwrap_or(Ordering::Equal));
+
+        let index_of = |value: f64| states.iter().position(|state| state.value == value);
+        let mut matrix = vec![vec![0; states.len()]; states.len()];
+        for (from, to) in switches {
+            if let (Some(from), Some(to)) = (index_of(from), index_of(to)) {
+                matrix[from][to] += 1;
+                stats.transitions += 1;
+            }
+        }
+
+        stats.longest_run = runs.iter().copied().reduce(|longest, run| {
+            if run.duration() > longest.duration() {
+                run
+            } else {
+                longest
+            }
+        });
+        stats.transition_matrix = matrix;
+        stats.states = states;
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::pack;
+    use crate::{TSPackStrategyType, TSSamples};
+
+    // examples/data/iot_valve_state_digital.csv: closed, open at 0.6 s,
+    // closed at 1.0 s, open from 1.3 s to the last sample at 1.7 s
+    fn valve() -> Vec<TSSamples> {
+        (0..18)
+            .map(|i| {
+                let open = (6..10).contains(&i) || i >= 13;
+                (i as f64 * 0.1, if open { 1.0 } else { 0.0 })
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn valve_open_time_and_switches() {
+        for strategy in [
+            TSPackStrategyType::TSPackRunLengthStrategy,
+            TSPackStrategyType::TSPackXorStrategy,
+        ] {
+            let packer = pack(valve(), strategy);
+            let stats = packer.state_stats(0.0, 1.7);
+
+            let values: Vec<(f64, usize)> =
+                stats.states.iter().map(|s| (s.value, s.runs)).collect();
+            assert_eq!(values, vec![(0.0, 2), (1.0, 2)]);
+            assert_close(stats.duration_of(0.0), 0.9);
+            assert_close(stats.duration_of(1.0), 0.8);
+            assert_eq!(stats.duration_of(2.0), 0.0);
+
+            assert_eq!(stats.transitions, 3);
+            assert_eq!(stats.transition_matrix, vec![vec![0, 2], vec![1, 0]]);
+
+            let longest = stats.longest_run.unwrap();
+            assert_eq!(longest.value, 0.0);
+            assert_close(longest.duration(), 0.6);
+        }
+    }
+
+    #[test]
+    fn runs_are_clipped_to_the_range() {
+        let packer = pack(valve(), TSPackStrategyType::TSPackRunLengthStrategy);
+
+        // open since 0.6 s when the range starts, closed at 1.0 s
+        let stats = packer.state_stats(0.8, 1.1);
+        assert_close(stats.duration_of(1.0), 0.2);
+        assert_close(stats.duration_of(0.0), 0.1);
+        assert_eq!(stats.transitions, 1);
+        assert_eq!(stats.transition_matrix, vec![vec![0, 0], vec![1, 0]]);
+
+        assert_eq!(packer.state_stats(1.0, 0.0).states, vec![]);
+        assert_eq!(packer.state_stats(5.0, 6.0).longest_run, None);
+    }
+
+    #[test]
+    fn nan_gaps_break_runs_without_transitions() {
+        let samples = vec![(0.0, 1.0), (1.0, f64::NAN), (2.0, 2.0), (3.0, 1.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackXorStrategy);
+        let stats = packer.state_stats(0.0, 3.0);
+
+        assert_eq!(stats.transitions, 1);
+        assert_eq!(stats.transition_matrix, vec![vec![0, 0], vec![1, 0]]);
+        assert_eq!(stats.duration_of(1.0), 1.0);
+        assert_eq!(stats.duration_of(2.0), 1.0);
+    }
+}
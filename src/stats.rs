@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use crate::TSPackStrategyType;
+
+/// Work done by one strategy of the chain, summed over all windows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TSPackStageStats {
+    pub strategy: TSPackStrategyType,
+    /// Entries (samples or ranges) this stage produced.
+    pub entries: usize,
+    pub elapsed: Duration,
+}
+
+/// What happened inside one [`crate::TimeSeriesDataPacker::pack_with_stats`] call.
+///
+/// `bytes_allocated` is estimated from the capacities of the buffers the
+/// pipeline creates (window index, owned stage outputs, merged output); it does not
+/// hook the global allocator.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TSPackStats {
+    pub samples: usize,
+    pub windows: usize,
+    pub stages: Vec<TSPackStageStats>,
+    /// Entries of all windows before cross-window merging.
+    pub window_entries: usize,
+    /// Ranges folded into their neighbour by `merge_adjacent_equal_value_ranges`.
+    pub merges: usize,
+    pub merge_elapsed: Duration,
+    /// Entries of the final packed series.
+    pub entries: usize,
+    pub bytes_allocated: usize,
+    pub elapsed: Duration,
+}
+
+/// Callbacks from inside [`crate::TimeSeriesDataPacker::pack_observed`], e.g.
+/// to forward pipeline data to your own logging or metrics. Every method has
+/// an empty default, so implement only the ones you need.
+pub trait TSPackObserver {
+    /// After `strategy` (position `stage` of the chain) ran over window `window`.
+    fn on_stage(
+        &mut self,
+        _window: usize,
+        _stage: usize,
+        _strategy: &TSPackStrategyType,
+        _entries: usize,
+        _elapsed: Duration,
+    ) {
+    }
+
+    /// After the whole chain packed window `window` of `samples` raw samples.
+    fn on_window(
+        &mut self,
+        _window: usize,
+        _window_start: f64,
+        _samples: usize,
+        _entries: usize,
+        _elapsed: Duration,
+    ) {
+    }
+
+    /// After adjacent equal-value ranges were merged across windows.
+    fn on_merge(&mut self, _entries_before: usize, _entries_after: usize, _elapsed: Duration) {}
+
+    /// Once the pack finished.
+    fn on_finish(&mut self, _stats: &TSPackStats) {}
+}
+
+/// No-op observer used by [`crate::TimeSeriesDataPacker::pack`].
+impl TSPackObserver for () {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TSPackAttributes, TimeSeriesDataPacker};
+
+    #[derive(Default)]
+    struct Recorder {
+        stages: Vec<(usize, usize, usize)>,
+        windows: Vec<(usize, f64, usize, usize)>,
+        merges: Vec<(usize, usize)>,
+        finished: bool,
+    }
+
+    impl TSPackObserver for Recorder {
+        fn on_stage(
+            &mut self,
+            window: usize,
+            stage: usize,
+            _strategy: &TSPackStrategyType,
+            entries: usize,
+            _elapsed: Duration,
+        ) {
+            self.stages.push((window, stage, entries));
+        }
+
+        fn on_window(
+            &mut self,
+            window: usize,
+            window_start: f64,
+            samples: usize,
+            entries: usize,
+            _elapsed: Duration,
+        ) {
+            self.windows.push((window, window_start, samples, entries));
+        }
+
+        fn on_merge(&mut self, entries_before: usize, entries_after: usize, _elapsed: Duration) {
+            self.merges.push((entries_before, entries_after));
+        }
+
+        fn on_finish(&mut self, _stats: &TSPackStats) {
+            self.finished = true;
+        }
+    }
+
+    fn attributes() -> TSPackAttributes {
+        TSPackAttributes {
+            strategy_types: vec![
+                TSPackStrategyType::TSPackSimilarValuesStrategy,
+                TSPackStrategyType::TSPackRunLengthStrategy,
+            ],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.01,
+        }
+    }
+
+    #[test]
+    fn counts_windows_stages_and_merges() {
+        // two windows holding the same value merge into a single range
+        let samples = vec![(0.0, 1.0), (0.5, 1.0), (2.0, 1.0), (2.5, 1.0), (2.7, 3.0)];
+
+        let mut packer = TimeSeriesDataPacker::new();
+        let (packed, stats) = packer.pack_with_stats(samples, attributes()).unwrap();
+
+        assert_eq!(packed, vec![((0.0, 2.5), 1.0), ((2.7, 2.7), 3.0)]);
+        assert_eq!(stats.samples, 5);
+        assert_eq!(stats.windows, 2);
+        assert_eq!(stats.stages.len(), 2);
+        assert_eq!(stats.stages[0].entries, 3);
+        assert_eq!(stats.window_entries, 3);
+        assert_eq!(stats.merges, 1);
+        assert_eq!(stats.entries, 2);
+        assert!(stats.bytes_allocated > 0);
+    }
+
+    #[test]
+    fn observer_gets_every_window_and_stage() {
+        let samples = vec![(0.0, 1.0), (0.5, 2.0), (2.0, 2.0)];
+
+        let mut recorder = Recorder::default();
+        let mut packer = TimeSeriesDataPacker::new();
+        packer
+            .pack_observed(samples, attributes(), &mut recorder)
+            .unwrap();
+
+        assert_eq!(
+            recorder.stages,
+            vec![(0, 0, 2), (0, 1, 2), (1, 0, 1), (1, 1, 1)]
+        );
+        assert_eq!(recorder.windows, vec![(0, 0.0, 2, 2), (1, 2.0, 1, 1)]);
+        assert_eq!(recorder.merges, vec![(3, 2)]);
+        assert!(recorder.finished);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::columns::TSSampleSource;
+use crate::decode::decode_window_iter;
+use crate::decode::TSPackDecodeIter;
+use crate::helpers::decode_window;
+use crate::helpers::finalize_epsilon;
+use crate::helpers::finalize_to_packed;
+use crate::helpers::max_step_hold_error;
+use crate::helpers::pack_samples;
+use crate::helpers::Representation;
+use crate::{TSPackAttributes, TSPackStrategyType, TSPackedSamples, TSSamples};
+
+/// Automatic strategy selection per window.
+///
+/// Every candidate strategy packs the window; the one with the fewest packed
+/// entries whose reconstruction stays within `max_error` (absolute, step-hold
+/// aligned by timestamp) wins. When no candidate meets the bound, the one with
+/// the smallest error is used. The choice is recorded in a header entry
+/// `((AUTO_WINDOW_TAG, entry_count), strategy_code)` in front of the window's
+/// entries, so decoding dispatches per window.
+pub struct TSPackAutoStrategy;
+
+/// Tag stored in `start_ts` of the per-window header entry.
+pub const AUTO_WINDOW_TAG: f64 = f64::MIN;
+
+/// Candidates tried when none are given explicitly (earlier wins ties).
+pub fn default_auto_candidates() -> Vec<TSPackStrategyType> {
+    vec![
+        TSPackStrategyType::TSPackRunLengthStrategy,
+        TSPackStrategyType::TSPackSimilarValuesStrategy,
+        TSPackStrategyType::TSPackMeanStrategy {
+            values_compression_percent: 5,
+        },
+        TSPackStrategyType::TSPackSimple8bStrategy,
+        TSPackStrategyType::TSPackDeltaStrategy,
+        TSPackStrategyType::TSPackDeltaOfDeltaStrategy,
+        TSPackStrategyType::TSPackXorStrategy,
+    ]
+}
+
+/// Stable code of a strategy kind stored in the window header.
+pub fn strategy_code(strategy: &TSPackStrategyType) -> f64 {
+    match strategy {
+        TSPackStrategyType::TSPackSimilarValuesStrategy => 0.0,
+        TSPackStrategyType::TSPackMeanStrategy { .. } => 1.0,
+        TSPackStrategyType::TSPackXorStrategy => 2.0,
+        TSPackStrategyType::TSPackDeltaStrategy => 3.0,
+        TSPackStrategyType::TSPackDeltaOfDeltaStrategy => 4.0,
+        TSPackStrategyType::TSPackRunLengthStrategy => 5.0,
+        TSPackStrategyType::TSPackSimple8bStrategy => 6.0,
+        TSPackStrategyType::TSPackAutoStrategy { .. } => 7.0,
+        TSPackStrategyType::TSPackCounterStrategy => 8.0,
+    }
+}
+
+/// Strategy whose decoder handles entries recorded with `code`. Mean ranges
+/// decode like any other range, so its percent does not matter here.
+pub fn strategy_from_code(code: f64) -> Option<TSPackStrategyType> {
+    match code as i64 {
+        0 => Some(TSPackStrategyType::TSPackSimilarValuesStrategy),
+        1 => Some(TSPackStrategyType::TSPackMeanStrategy {
+            values_compression_percent: 0,
+        }),
+        2 => Some(TSPackStrategyType::TSPackXorStrategy),
+        3 => Some(TSPackStrategyType::TSPackDeltaStrategy),
+        4 => Some(TSPackStrategyType::TSPackDeltaOfDeltaStrategy),
+        5 => Some(TSPackStrategyType::TSPackRunLengthStrategy),
+        6 => Some(TSPackStrategyType::TSPackSimple8bStrategy),
+        8 => Some(TSPackStrategyType::TSPackCounterStrategy),
+        _ => None,
+    }
+}
+
+impl TSPackAutoStrategy {
+    pub fn pack<S: TSSampleSource + ?Sized>(
+        samples: &S,
+        candidates: &[TSPackStrategyType],
+        max_error: f64,
+        precision_epsilon: f64,
+    ) -> Vec<TSPackedSamples> {
+        if samples.is_empty() {
+            return Vec::new();
+        }
+
+        // (meets_bound, entries, error, strategy, packed)
+        let mut best: Option<(bool, usize, f64, &TSPackStrategyType, Vec<TSPackedSamples>)> = None;
+
+        for candidate in candidates {
+            let attributes = TSPackAttributes {
+                strategy_types: vec![candidate.clone()],
+                microseconds_time_window: u64::MAX,
+                precision_epsilon,
+            };
+            let packed = finalize_to_packed(
+                Representation::Packed(pack_samples(samples, candidate, precision_epsilon)),
+                finalize_epsilon(&attributes),
+            );
+            let decoded = decode_window(&packed, &attributes.strategy_types, precision_epsilon);
+            let error = max_step_hold_error(samples, &decoded);
+            let meets_bound = error <= max_error;
+
+            let better = match &best {
+                None => true,
+                Some((best_meets, best_len, best_error, _, _)) => match (meets_bound, best_meets) {
+                    (true, false) => true,
+                    (true, true) => packed.len() < *best_len,
+                    (false, false) => error < *best_error,
+                    (false, true) => false,
+                },
+            };
+
+            if better {
+                best = Some((meets_bound, packed.len(), error, candidate, packed));
+            }
+        }
+
+        let Some((_, len, _, strategy, packed)) = best else {
+            return Vec::new();
+        };
+
+        let mut result = Vec::with_capacity(len + 1);
+        result.push(((AUTO_WINDOW_TAG, len as f64), strategy_code(strategy)));
+        result.extend(packed);
+        result
+    }
+
+    /// Decodes every recorded window with the decoder of the strategy chosen for it.
+    pub fn unpack(packed: &[TSPackedSamples], precision_epsilon: f64) -> Vec<TSSamples> {
+        Self::iter_unpack(packed, precision_epsilon).collect()
+    }
+
+    /// Lazy [`Self::unpack`]: a window's decoder starts when the previous
+    /// window is exhausted.
+    pub fn iter_unpack(
+        packed: &[TSPackedSamples],
+        precision_epsilon: f64,
+    ) -> TSPackAutoUnpackIter<'_> {
+        TSPackAutoUnpackIter {
+            packed,
+            index: 0,
+            precision_epsilon,
+            window: None,
+        }
+    }
+
+    /// Strategy chosen for each recorded window, in order.
+    pub fn chosen_strategies(packed: &[TSPackedSamples]) -> Vec<TSPackStrategyType> {
+        packed
+            .iter()
+            .filter(|((tag, _), _)| *tag == AUTO_WINDOW_TAG)
+            .filter_map(|(_, code)| strategy_from_code(*code))
+            .collect()
+    }
+}
+
+/// Iterator returned by [`TSPackAutoStrategy::iter_unpack`].
+#[derive(Debug, Clone)]
+pub struct TSPackAutoUnpackIter<'a> {
+    packed: &'a [TSPackedSamples],
+    // next entry to read, a window header unless the series is malformed
+    index: usize,
+    precision_epsilon: f64,
+    window: Option<TSPackDecodeIter<'a>>,
+}
+
+impl Iterator for TSPackAutoUnpackIter<'_> {
+    type Item = TSSamples;
+
+    fn next(&mut self) -> Option<TSSamples> {
+        loop {
+            if let Some(sample) = self.window.as_mut().and_then(Iterator::next) {
+                return Some(sample);
+            }
+            self.window = None;
+
+            let &((tag, len), code) = self.packed.get(self.index)?;
+            self.index += 1;
+
+            if tag != AUTO_WINDOW_TAG {
+                continue;
+            }
+
+            let end = (self.index + len as usize).min(self.packed.len());
+            if let Some(strategy) = strategy_from_code(code) {
+                self.window = Some(decode_window_iter(
+                    &self.packed[self.index..end],
+                    &[strategy],
+                    self.precision_epsilon,
+                ));
+            }
+            self.index = end;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TSPackAttributes, TimeSeriesDataPacker};
+
+    fn auto_attributes(max_error: f64) -> TSPackAttributes {
+        TSPackAttributes {
+            strategy_types: vec![TSPackStrategyType::TSPackAutoStrategy {
+                candidates: default_auto_candidates(),
+                max_error,
+            }],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 1e-3,
+        }
+    }
+
+    #[test]
+    fn picks_strategy_per_window() {
+        let mut samples: Vec<TSSamples> = (0..10).map(|i| (i as f64 * 0.1, 1.0)).collect();
+        samples.extend((0..10).map(|i| (2.0 + i as f64 * 0.1, (i * i) as f64 * 0.37)));
+
+        let mut packer = TimeSeriesDataPacker::new();
+        let packed = packer.pack(samples.clone(), auto_attributes(0.0)).unwrap();
+
+        let chosen = TSPackAutoStrategy::chosen_strategies(packed);
+        assert_eq!(chosen.len(), 2);
+        assert_eq!(chosen[0], TSPackStrategyType::TSPackRunLengthStrategy);
+        assert_ne!(chosen[1], TSPackStrategyType::TSPackRunLengthStrategy);
+
+        // constant window: one header and one run
+        assert_eq!(packed[0], ((AUTO_WINDOW_TAG, 1.0), 5.0));
+        assert_eq!(packed[1], ((0.0, 0.9), 1.0));
+
+        let decoded = TSPackAutoStrategy::unpack(packed, 1e-3);
+        assert_eq!(max_step_hold_error(&samples, &decoded), 0.0);
+    }
+
+    #[test]
+    fn error_bound_allows_lossy_candidates() {
+        let samples: Vec<TSSamples> = (0..20)
+            .map(|i| (i as f64 * 0.01, 100.0 + if i % 2 == 0 { 0.5 } else { -0.5 }))
+            .collect();
+
+        let lossless = TSPackAutoStrategy::pack(&samples, &default_auto_candidates(), 0.0, 1e-3);
+        let lossy = TSPackAutoStrategy::pack(&samples, &default_auto_candidates(), 1.0, 1e-3);
+
+        assert!(lossy.len() < lossless.len());
+        assert!(max_step_hold_error(&samples, &TSPackAutoStrategy::unpack(&lossy, 1e-3)) <= 1.0);
+    }
+
+    #[test]
+    fn packer_unpack_dispatches_per_window() {
+        let samples = vec![(0.0, 1.0), (0.5, 1.0), (2.0, 3.0), (2.1, 7.5)];
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer.pack(samples.clone(), auto_attributes(0.0)).unwrap();
+        let (_attrs, unpacked) = packer.unpack();
+
+        assert_eq!(unpacked, samples);
+    }
+
+    #[test]
+    fn packs_ranges_of_an_earlier_stage() {
+        // 5 plateaus of 10 samples each
+        let samples: Vec<TSSamples> = (0..50).map(|i| (i as f64 * 0.1, (i / 10) as f64)).collect();
+
+        let mut attributes = auto_attributes(0.0);
+        attributes
+            .strategy_types
+            .insert(0, TSPackStrategyType::TSPackSimilarValuesStrategy);
+
+        let mut packer = TimeSeriesDataPacker::new();
+        packer.pack(samples.clone(), attributes).unwrap();
+
+        assert!(!packer.packed_samples().is_empty());
+        let decoded = packer.decode();
+        assert!(!decoded.is_empty());
+        assert_eq!(max_step_hold_error(&samples, &decoded), 0.0);
+    }
+}
@@ -0,0 +1,114 @@
+use std::slice::Iter;
+
+use crate::columns::TSSampleSource;
+use crate::{TSPackedSamples, TSSamples};
+
+/// Monotonic counter encoding for energy meters, production counters and
+/// other series that only grow until they reset.
+///
+/// The first sample is stored raw, every following one as its non-negative
+/// increment over the previous sample. A sample below the previous one is a
+/// counter reset: a marker entry holding the value reached before the reset
+/// is followed by the new sample stored raw, instead of the large negative
+/// delta [`crate::TSPackDeltaStrategy`] would record.
+pub struct TSPackCounterStrategy;
+
+/// Tag stored in `start_ts` and `end_ts` of reset marker entries.
+pub const COUNTER_RESET_TAG: f64 = f64::NEG_INFINITY;
+
+impl TSPackCounterStrategy {
+    pub fn pack<S: TSSampleSource + ?Sized>(samples: &S) -> Vec<TSPackedSamples> {
+        if samples.is_empty() {
+            return Vec::new();
+        }
+
+        let mut packed = Vec::with_capacity(samples.len());
+
+        let (t0, v0) = samples.sample(0);
+        packed.push(((t0, t0), v0));
+
+        let mut prev = v0;
+
+        for (t, v) in samples.iter().skip(1) {
+            if v < prev {
+                packed.push(((COUNTER_RESET_TAG, COUNTER_RESET_TAG), prev));
+                packed.push(((t, t), v));
+            } else {
+                packed.push(((t, t), v - prev));
+            }
+            prev = v;
+        }
+
+        packed
+    }
+
+    pub fn unpack(packed: &[TSPackedSamples]) -> Vec<TSSamples> {
+        Self::iter_unpack(packed).collect()
+    }
+
+    /// Lazy [`Self::unpack`]: adds one increment per `next()` call.
+    pub fn iter_unpack(packed: &[TSPackedSamples]) -> TSPackCounterUnpackIter<'_> {
+        TSPackCounterUnpackIter {
+            packed: packed.iter(),
+            prev: None,
+        }
+    }
+
+    /// Reset points of one packed window: timestamp of the first sample after
+    /// each reset and the value the counter reached before it.
+    ///
+    /// Every window starts from a raw anchor, so a reset between two windows
+    /// has no marker. Pass the last value of the previous window as
+    /// `previous` to report a drop at the start of `packed` as well.
+    pub fn resets(packed: &[TSPackedSamples], previous: Option<f64>) -> Vec<TSSamples> {
+        let anchor = packed
+            .first()
+            .zip(previous)
+            .and_then(|(&((t0, _), v0), previous)| {
+                (t0 != COUNTER_RESET_TAG && v0 < previous).then_some((t0, previous))
+            });
+
+        anchor
+            .into_iter()
+            .chain(
+                packed
+                    .windows(2)
+                    .filter(|pair| pair[0].0 .0 == COUNTER_RESET_TAG)
+                    .map(|pair| (pair[1].0 .0, pair[0].1)),
+            )
+            .collect()
+    }
+}
+
+/// Iterator returned by [`TSPackCounterStrategy::iter_unpack`].
+#[derive(Debug, Clone)]
+pub struct TSPackCounterUnpackIter<'a> {
+    packed: Iter<'a, TSPackedSamples>,
+    prev: Option<f64>,
+}
+
+impl Iterator for TSPackCounterUnpackIter<'_> {
+    type Item = TSSamples;
+
+    fn next(&mut self) -> Option<TSSamples> {
+        let mut entry = self.packed.next()?;
+
+        let v = if entry.0 .0 == COUNTER_RESET_TAG {
+            entry = self.packed.next()?;
+            entry.1
+        } else {
+            match self.prev {
+                None => entry.1,
+                Some(prev) => prev + entry.1,
+            }
+        };
+        self.prev = Some(v);
+
+        Some((entry.0 .0, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, upper) = self.packed.size_hint();
+        (0, upper)
+    }
+}
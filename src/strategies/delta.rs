@@ -1,21 +1,24 @@
+use std::slice::Iter;
+
+use crate::columns::TSSampleSource;
 use crate::{TSPackedSamples, TSSamples};
 
 pub struct TSPackDeltaStrategy;
 
 impl TSPackDeltaStrategy {
-    pub fn pack(samples: &[TSSamples]) -> Vec<TSPackedSamples> {
+    pub fn pack<S: TSSampleSource + ?Sized>(samples: &S) -> Vec<TSPackedSamples> {
         if samples.is_empty() {
             return Vec::new();
         }
 
         let mut packed = Vec::with_capacity(samples.len());
 
-        let (t0, v0) = samples[0];
+        let (t0, v0) = samples.sample(0);
         packed.push(((t0, t0), v0));
 
         let mut prev = v0;
 
-        for &(t, v) in &samples[1..] {
+        for (t, v) in samples.iter().skip(1) {
             let delta = v - prev;
             packed.push(((t, t), delta));
             prev = v;
@@ -25,23 +28,43 @@ impl TSPackDeltaStrategy {
     }
 
     pub fn unpack(packed: &[TSPackedSamples]) -> Vec<TSSamples> {
-        if packed.is_empty() {
-            return Vec::new();
+        Self::iter_unpack(packed).collect()
+    }
+
+    /// Lazy [`Self::unpack`]: adds one delta per `next()` call.
+    pub fn iter_unpack(packed: &[TSPackedSamples]) -> TSPackDeltaUnpackIter<'_> {
+        TSPackDeltaUnpackIter {
+            packed: packed.iter(),
+            prev: None,
         }
+    }
+}
 
-        let mut result = Vec::with_capacity(packed.len());
+/// Iterator returned by [`TSPackDeltaStrategy::iter_unpack`].
+#[derive(Debug, Clone)]
+pub struct TSPackDeltaUnpackIter<'a> {
+    packed: Iter<'a, TSPackedSamples>,
+    prev: Option<f64>,
+}
 
-        let ((t0, _), v0) = packed[0];
-        result.push((t0, v0));
+impl Iterator for TSPackDeltaUnpackIter<'_> {
+    type Item = TSSamples;
 
-        let mut prev = v0;
+    fn next(&mut self) -> Option<TSSamples> {
+        let &((t, _), delta) = self.packed.next()?;
 
-        for &((t, _), delta) in &packed[1..] {
-            let v = prev + delta;
-            result.push((t, v));
-            prev = v;
-        }
+        let v = match self.prev {
+            None => delta,
+            Some(prev) => prev + delta,
+        };
+        self.prev = Some(v);
 
-        result
+        Some((t, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.packed.size_hint()
     }
 }
+
+impl ExactSizeIterator for TSPackDeltaUnpackIter<'_> {}
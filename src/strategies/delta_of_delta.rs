@@ -1,3 +1,6 @@
+use std::slice::Iter;
+
+use crate::columns::TSSampleSource;
 use crate::{TSPackedSamples, TSSamples};
 
 /// Delta-of-delta encoding for `f64` value series.
@@ -8,82 +11,98 @@ use crate::{TSPackedSamples, TSSamples};
 pub struct TSPackDeltaOfDeltaStrategy;
 
 impl TSPackDeltaOfDeltaStrategy {
-    pub fn pack(samples: &[TSSamples]) -> Vec<TSPackedSamples> {
+    pub fn pack<S: TSSampleSource + ?Sized>(samples: &S) -> Vec<TSPackedSamples> {
         if samples.is_empty() {
             return Vec::new();
         }
 
         let mut packed = Vec::with_capacity(samples.len());
 
-        let (t0, v0) = samples[0];
+        let (t0, v0) = samples.sample(0);
         packed.push(((t0, t0), v0));
 
         if samples.len() == 1 {
             return packed;
         }
 
-        let v1 = samples[0].1;
-        let v2 = samples[1].1;
-        let first_delta = v2 - v1;
-        packed.push(((samples[1].0, samples[1].0), first_delta));
+        let (t1, v1) = samples.sample(1);
+        let first_delta = v1 - v0;
+        packed.push(((t1, t1), first_delta));
 
         if samples.len() == 2 {
             return packed;
         }
 
         let mut prev_delta = first_delta;
+        let mut vim1 = v1;
 
-        for i in 2..samples.len() {
-            let vi = samples[i].1;
-            let vim1 = samples[i - 1].1;
+        for (ti, vi) in samples.iter().skip(2) {
             let delta = vi - vim1;
             let delta_of_delta = delta - prev_delta;
-            packed.push(((samples[i].0, samples[i].0), delta_of_delta));
+            packed.push(((ti, ti), delta_of_delta));
             prev_delta = delta;
+            vim1 = vi;
         }
 
         packed
     }
 
     pub fn unpack(packed: &[TSPackedSamples]) -> Vec<TSSamples> {
-        if packed.is_empty() {
-            return Vec::new();
-        }
-
-        let mut samples = Vec::with_capacity(packed.len());
-
-        let ((t0, _), v0) = packed[0];
-        samples.push((t0, v0));
+        Self::iter_unpack(packed).collect()
+    }
 
-        if packed.len() == 1 {
-            return samples;
+    /// Lazy [`Self::unpack`]: integrates one delta-of-delta per `next()` call.
+    pub fn iter_unpack(packed: &[TSPackedSamples]) -> TSPackDeltaOfDeltaUnpackIter<'_> {
+        TSPackDeltaOfDeltaUnpackIter {
+            packed: packed.iter(),
+            last_value: None,
+            prev_delta: None,
         }
+    }
+}
 
-        let ((t1, _), first_delta) = packed[1];
-        let v1 = v0 + first_delta;
-        samples.push((t1, v1));
-
-        if packed.len() == 2 {
-            return samples;
-        }
+/// Iterator returned by [`TSPackDeltaOfDeltaStrategy::iter_unpack`].
+#[derive(Debug, Clone)]
+pub struct TSPackDeltaOfDeltaUnpackIter<'a> {
+    packed: Iter<'a, TSPackedSamples>,
+    last_value: Option<f64>,
+    prev_delta: Option<f64>,
+}
 
-        let mut prev_delta = first_delta;
-        let mut last_value = v1;
+impl Iterator for TSPackDeltaOfDeltaUnpackIter<'_> {
+    type Item = TSSamples;
+
+    fn next(&mut self) -> Option<TSSamples> {
+        let &((ts, _), encoded) = self.packed.next()?;
+
+        let value = match (self.last_value, self.prev_delta) {
+            // first entry: raw value
+            (None, _) => encoded,
+            // second entry: first delta
+            (Some(last_value), None) => {
+                self.prev_delta = Some(encoded);
+                last_value + encoded
+            }
+            (Some(last_value), Some(prev_delta)) => {
+                let delta = prev_delta + encoded;
+                self.prev_delta = Some(delta);
+                last_value + delta
+            }
+        };
+        self.last_value = Some(value);
 
-        for &((ts, _), delta_of_delta) in &packed[2..] {
-            let delta = prev_delta + delta_of_delta;
-            let value = last_value + delta;
-            samples.push((ts, value));
-            prev_delta = delta;
-            last_value = value;
-        }
+        Some((ts, value))
+    }
 
-        samples
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.packed.size_hint()
     }
 }
 
+impl ExactSizeIterator for TSPackDeltaOfDeltaUnpackIter<'_> {}
+
 /// Convenience alias for [`TSPackDeltaOfDeltaStrategy::pack`].
-pub fn delta_of_delta_pack(samples: &[TSSamples]) -> Vec<TSPackedSamples> {
+pub fn delta_of_delta_pack<S: TSSampleSource + ?Sized>(samples: &S) -> Vec<TSPackedSamples> {
     TSPackDeltaOfDeltaStrategy::pack(samples)
 }
 
@@ -176,7 +195,7 @@ mod tests {
         };
 
         let packed = packer.pack(samples.clone(), attrs).unwrap();
-        let recovered = TSPackDeltaOfDeltaStrategy::unpack(&packed);
+        let recovered = TSPackDeltaOfDeltaStrategy::unpack(packed);
 
         assert_samples_eq(&samples, &recovered);
     }
@@ -195,6 +214,6 @@ mod tests {
 
         let repacked = packer.pack(samples.clone(), attrs).unwrap();
         assert_eq!(first_pass.len(), repacked.len());
-        assert_samples_eq(&samples, &TSPackDeltaOfDeltaStrategy::unpack(&repacked));
+        assert_samples_eq(&samples, &TSPackDeltaOfDeltaStrategy::unpack(repacked));
     }
 }
@@ -1,12 +1,17 @@
+use crate::columns::TSSampleSource;
 use crate::helpers::merge_adjacent_equal_value_ranges;
-use crate::{TSPackedSamples, TSSamples};
+use crate::TSPackedSamples;
 
 #[inline]
 fn approx_equal(a: f64, b: f64, eps: f64) -> bool {
     (a - b).abs() <= eps
 }
 
-pub fn mean_pack(samples: &[TSSamples], percent: u8, eps: f64) -> Vec<TSPackedSamples> {
+pub fn mean_pack<S: TSSampleSource + ?Sized>(
+    samples: &S,
+    percent: u8,
+    eps: f64,
+) -> Vec<TSPackedSamples> {
     if samples.is_empty() {
         return Vec::new();
     }
@@ -25,7 +30,7 @@ pub fn mean_pack(samples: &[TSSamples], percent: u8, eps: f64) -> Vec<TSPackedSa
     let mut group_start_ts: Option<f64> = None;
     let mut group_end_ts: Option<f64> = None;
 
-    for &(ts, v) in samples {
+    for (ts, v) in samples.iter() {
         if (v >= lower && v <= upper) || approx_equal(v, avg, eps) {
             if group_start_ts.is_none() {
                 group_start_ts = Some(ts);
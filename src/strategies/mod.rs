@@ -1,3 +1,5 @@
+pub mod auto;
+pub mod counter;
 pub mod delta;
 pub mod delta_of_delta;
 pub mod mean_based_compression;
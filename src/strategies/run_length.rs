@@ -1,3 +1,6 @@
+use std::slice::Iter;
+
+use crate::columns::TSSampleSource;
 use crate::{TSPackedSamples, TSSamples};
 
 /// Run-length encoding for consecutive identical `f64` values.
@@ -13,18 +16,19 @@ fn values_equal(a: f64, b: f64) -> bool {
 }
 
 impl TSPackRunLengthStrategy {
-    pub fn pack(samples: &[TSSamples]) -> Vec<TSPackedSamples> {
+    pub fn pack<S: TSSampleSource + ?Sized>(samples: &S) -> Vec<TSPackedSamples> {
         if samples.is_empty() {
             return Vec::new();
         }
 
         let mut packed = Vec::new();
 
-        let mut run_start_ts = samples[0].0;
-        let mut prev_ts = samples[0].0;
-        let mut current_value = samples[0].1;
+        let (first_ts, first_val) = samples.sample(0);
+        let mut run_start_ts = first_ts;
+        let mut prev_ts = first_ts;
+        let mut current_value = first_val;
 
-        for &(ts, val) in &samples[1..] {
+        for (ts, val) in samples.iter().skip(1) {
             if values_equal(val, current_value) {
                 prev_ts = ts;
             } else {
@@ -42,21 +46,52 @@ impl TSPackRunLengthStrategy {
     /// Expands each run to its start and end timestamp (same semantics as
     /// [`crate::TimeSeriesDataPacker::unpack`]).
     pub fn unpack(packed: &[TSPackedSamples]) -> Vec<TSSamples> {
-        let mut samples = Vec::with_capacity(packed.len() * 2);
+        Self::iter_unpack(packed).collect()
+    }
 
-        for &((start, end), val) in packed {
-            samples.push((start, val));
-            if end != start {
-                samples.push((end, val));
-            }
+    /// Lazy [`Self::unpack`]. Also decodes Similar Values and Mean output,
+    /// which are plain ranges too.
+    pub fn iter_unpack(packed: &[TSPackedSamples]) -> TSPackRunLengthUnpackIter<'_> {
+        TSPackRunLengthUnpackIter {
+            packed: packed.iter(),
+            pending_end: None,
         }
+    }
+}
+
+/// Iterator returned by [`TSPackRunLengthStrategy::iter_unpack`].
+#[derive(Debug, Clone)]
+pub struct TSPackRunLengthUnpackIter<'a> {
+    packed: Iter<'a, TSPackedSamples>,
+    // end point of the current run, yielded after its start point
+    pending_end: Option<TSSamples>,
+}
+
+impl Iterator for TSPackRunLengthUnpackIter<'_> {
+    type Item = TSSamples;
 
-        samples
+    fn next(&mut self) -> Option<TSSamples> {
+        if let Some(end) = self.pending_end.take() {
+            return Some(end);
+        }
+
+        let &((start, end), val) = self.packed.next()?;
+        if end != start {
+            self.pending_end = Some((end, val));
+        }
+
+        Some((start, val))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = usize::from(self.pending_end.is_some());
+        let runs = self.packed.len();
+        (runs + pending, Some(runs * 2 + pending))
     }
 }
 
 /// Convenience alias for [`TSPackRunLengthStrategy::pack`].
-pub fn rle_pack(samples: &[TSSamples]) -> Vec<TSPackedSamples> {
+pub fn rle_pack<S: TSSampleSource + ?Sized>(samples: &S) -> Vec<TSPackedSamples> {
     TSPackRunLengthStrategy::pack(samples)
 }
 
@@ -143,7 +178,7 @@ mod tests {
         let packed = packer.pack(samples, attrs).unwrap();
         assert_eq!(packed.len(), 2);
 
-        let unpacked = TSPackRunLengthStrategy::unpack(&packed);
+        let unpacked = TSPackRunLengthStrategy::unpack(packed);
         assert_eq!(unpacked[0], (0.0, 1.0));
         assert_eq!(unpacked[1], (0.1, 1.0));
         assert_eq!(unpacked[2], (0.2, 2.0));
@@ -1,8 +1,5 @@
-use crate::TSPackAttributes;
-use crate::TSPackStrategyType;
+use crate::columns::TSSampleSource;
 use crate::TSPackedSamples;
-use crate::TSSamples;
-use crate::TimeSeriesDataPacker;
 
 #[inline]
 fn approx_equal(a: f64, b: f64, eps: f64) -> bool {
@@ -15,18 +12,22 @@ fn approx_equal(a: f64, b: f64, eps: f64) -> bool {
     (a - b).abs() <= eps
 }
 
-pub fn similar_values_pack(samples: &[TSSamples], eps: f64) -> Vec<TSPackedSamples> {
+pub fn similar_values_pack<S: TSSampleSource + ?Sized>(
+    samples: &S,
+    eps: f64,
+) -> Vec<TSPackedSamples> {
     if samples.is_empty() {
         return Vec::new();
     }
 
     let mut result: Vec<TSPackedSamples> = Vec::new();
 
-    let mut run_start_ts = samples[0].0;
-    let mut prev_ts = samples[0].0;
-    let mut current_value = samples[0].1;
+    let (first_ts, first_val) = samples.sample(0);
+    let mut run_start_ts = first_ts;
+    let mut prev_ts = first_ts;
+    let mut current_value = first_val;
 
-    for &(ts, val) in &samples[1..] {
+    for (ts, val) in samples.iter().skip(1) {
         if approx_equal(val, current_value, eps) {
             prev_ts = ts;
         } else {
@@ -45,7 +46,7 @@ pub fn similar_values_pack(samples: &[TSSamples], eps: f64) -> Vec<TSPackedSampl
 
 #[cfg(test)]
 mod similar_values_pack_tests {
-    use super::*;
+    use crate::{TSPackAttributes, TSPackStrategyType, TSSamples, TimeSeriesDataPacker};
 
     #[test]
     fn test_similar_values_pack_with_3_digits_precision() {
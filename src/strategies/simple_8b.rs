@@ -1,3 +1,6 @@
+use std::slice::Iter;
+
+use crate::columns::TSSampleSource;
 use crate::{TSPackedSamples, TSSamples};
 
 /// Simple-8b variable-bit integer packing applied to scaled value deltas.
@@ -101,36 +104,87 @@ pub fn simple8b_encode(values: &[u64]) -> Vec<u64> {
 }
 
 pub fn simple8b_decode(words: &[u64]) -> Vec<u64> {
-    let mut values = Vec::new();
+    simple8b_decode_iter(words.iter().copied()).collect()
+}
 
-    for &word in words {
-        let mode = (word >> 60) as usize;
-        let (count, bits) = SIMPLE8B_MODES[mode];
-        let mut shift = 0;
+/// Lazy [`simple8b_decode`]: unpacks a word only when its first value is requested.
+pub fn simple8b_decode_iter<I: IntoIterator<Item = u64>>(
+    words: I,
+) -> TSPackSimple8bDecodeIter<I::IntoIter> {
+    TSPackSimple8bDecodeIter {
+        words: words.into_iter(),
+        word: 0,
+        remaining: 0,
+        bits: 0,
+        shift: 0,
+    }
+}
 
-        for _ in 0..count {
-            if bits == 0 {
-                values.push(0);
-            } else {
-                let mask = (1u64 << bits) - 1;
-                values.push((word >> shift) & mask);
-                shift += bits;
-            }
+/// Iterator returned by [`simple8b_decode_iter`].
+#[derive(Debug, Clone)]
+pub struct TSPackSimple8bDecodeIter<I> {
+    words: I,
+    word: u64,
+    // values left in `word`
+    remaining: usize,
+    bits: u8,
+    shift: u8,
+}
+
+impl<I: Iterator<Item = u64>> Iterator for TSPackSimple8bDecodeIter<I> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.remaining == 0 {
+            self.word = self.words.next()?;
+            let (count, bits) = SIMPLE8B_MODES[(self.word >> 60) as usize];
+            self.remaining = count;
+            self.bits = bits;
+            self.shift = 0;
+        }
+
+        self.remaining -= 1;
+
+        if self.bits == 0 {
+            return Some(0);
         }
+
+        let mask = (1u64 << self.bits) - 1;
+        let value = (self.word >> self.shift) & mask;
+        self.shift += self.bits;
+        Some(value)
     }
+}
 
-    values
+/// Payloads of the Simple-8b words tagged with `tag`, in order.
+#[derive(Debug, Clone)]
+pub struct TSPackSimple8bWords<'a> {
+    packed: Iter<'a, TSPackedSamples>,
+    tag: f64,
+}
+
+impl Iterator for TSPackSimple8bWords<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.packed
+            .find(|((tag, _), _)| *tag == self.tag)
+            .map(|(_, word)| word.to_bits())
+    }
 }
 
 impl TSPackSimple8bStrategy {
-    pub fn pack(samples: &[TSSamples], precision_epsilon: f64) -> Vec<TSPackedSamples> {
+    pub fn pack<S: TSSampleSource + ?Sized>(
+        samples: &S,
+        precision_epsilon: f64,
+    ) -> Vec<TSPackedSamples> {
         if samples.is_empty() {
             return Vec::new();
         }
 
         let scale = scale_from_epsilon(precision_epsilon);
-        let (first_ts, first_value) = samples[0];
-        let last_ts = samples.last().map(|(ts, _)| *ts).unwrap_or(first_ts);
+        let (first_ts, first_value) = samples.sample(0);
+        let last_ts = samples.timestamp(samples.len() - 1);
 
         let mut packed = Vec::new();
         packed.push(((first_ts, last_ts), first_value));
@@ -144,7 +198,7 @@ impl TSPackSimple8bStrategy {
         let mut prev_value = first_value;
         let mut prev_ts = first_ts;
 
-        for &(ts, value) in &samples[1..] {
+        for (ts, value) in samples.iter().skip(1) {
             let scaled_delta = ((value - prev_value) * scale).round() as i64;
             value_deltas.push(zigzag_encode(scaled_delta));
 
@@ -167,53 +221,71 @@ impl TSPackSimple8bStrategy {
     }
 
     pub fn unpack(packed: &[TSPackedSamples], precision_epsilon: f64) -> Vec<TSSamples> {
-        if packed.is_empty() {
-            return Vec::new();
-        }
-
-        let scale = scale_from_epsilon(precision_epsilon);
-        let ((first_ts, _), first_value) = packed[0];
-
-        let value_words: Vec<u64> = packed
-            .iter()
-            .filter(|((tag, _), _)| *tag == SIMPLE8B_VALUE_WORD_TAG)
-            .map(|(_, value)| value.to_bits())
-            .collect();
+        Self::iter_unpack(packed, precision_epsilon).collect()
+    }
 
-        let time_words: Vec<u64> = packed
-            .iter()
-            .filter(|((tag, _), _)| *tag == SIMPLE8B_TIME_WORD_TAG)
-            .map(|(_, value)| value.to_bits())
-            .collect();
+    /// Lazy [`Self::unpack`]: value and timestamp words are decoded side by
+    /// side, one delta per `next()` call.
+    pub fn iter_unpack(
+        packed: &[TSPackedSamples],
+        precision_epsilon: f64,
+    ) -> TSPackSimple8bUnpackIter<'_> {
+        let anchor = packed
+            .first()
+            .map(|&((first_ts, _), first_value)| (first_ts, first_value));
+
+        let words = |tag| TSPackSimple8bWords {
+            packed: packed.iter(),
+            tag,
+        };
 
-        let value_deltas = simple8b_decode(&value_words);
-        let time_deltas_us = simple8b_decode(&time_words);
+        TSPackSimple8bUnpackIter {
+            anchor,
+            value_deltas: simple8b_decode_iter(words(SIMPLE8B_VALUE_WORD_TAG)),
+            time_deltas_us: simple8b_decode_iter(words(SIMPLE8B_TIME_WORD_TAG)),
+            current: anchor.unwrap_or_default(),
+            scale: scale_from_epsilon(precision_epsilon),
+        }
+    }
+}
 
-        let mut samples = Vec::with_capacity(value_deltas.len() + 1);
-        samples.push((first_ts, first_value));
+/// Iterator returned by [`TSPackSimple8bStrategy::iter_unpack`].
+#[derive(Debug, Clone)]
+pub struct TSPackSimple8bUnpackIter<'a> {
+    // first sample, yielded before any delta
+    anchor: Option<TSSamples>,
+    value_deltas: TSPackSimple8bDecodeIter<TSPackSimple8bWords<'a>>,
+    time_deltas_us: TSPackSimple8bDecodeIter<TSPackSimple8bWords<'a>>,
+    current: TSSamples,
+    scale: f64,
+}
 
-        let mut current_value = first_value;
-        let mut current_ts = first_ts;
+impl Iterator for TSPackSimple8bUnpackIter<'_> {
+    type Item = TSSamples;
 
-        for index in 0..value_deltas.len() {
-            let delta = zigzag_decode(value_deltas[index]) as f64 / scale;
-            current_value += delta;
+    fn next(&mut self) -> Option<TSSamples> {
+        if let Some(anchor) = self.anchor.take() {
+            return Some(anchor);
+        }
 
-            if index < time_deltas_us.len() {
-                current_ts += time_deltas_us[index] as f64 / 1_000_000.0;
-            } else {
-                current_ts += 1.0;
-            }
+        let value_delta = self.value_deltas.next()?;
+        let (current_ts, current_value) = &mut self.current;
 
-            samples.push((current_ts, current_value));
-        }
+        *current_value += zigzag_decode(value_delta) as f64 / self.scale;
+        *current_ts += match self.time_deltas_us.next() {
+            Some(delta_us) => delta_us as f64 / 1_000_000.0,
+            None => 1.0,
+        };
 
-        samples
+        Some(self.current)
     }
 }
 
 /// Convenience alias for [`TSPackSimple8bStrategy::pack`].
-pub fn simple8b_pack(samples: &[TSSamples], precision_epsilon: f64) -> Vec<TSPackedSamples> {
+pub fn simple8b_pack<S: TSSampleSource + ?Sized>(
+    samples: &S,
+    precision_epsilon: f64,
+) -> Vec<TSPackedSamples> {
     TSPackSimple8bStrategy::pack(samples, precision_epsilon)
 }
 
@@ -315,7 +387,7 @@ mod tests {
         };
 
         let packed = packer.pack(samples.clone(), attrs.clone()).unwrap();
-        let recovered = TSPackSimple8bStrategy::unpack(&packed, attrs.precision_epsilon);
+        let recovered = TSPackSimple8bStrategy::unpack(packed, attrs.precision_epsilon);
 
         assert_values_close(&samples, &recovered, 1e-3);
     }
@@ -337,8 +409,31 @@ mod tests {
         assert_eq!(first_pass.len(), repacked.len());
         assert_values_close(
             &samples,
-            &TSPackSimple8bStrategy::unpack(&repacked, epsilon),
+            &TSPackSimple8bStrategy::unpack(repacked, epsilon),
             1e-3,
         );
     }
+
+    #[test]
+    fn lazy_decode_reads_words_on_demand() {
+        let values: Vec<u64> = (0..500).map(|i| i % 9).collect();
+        let words = simple8b_encode(&values);
+
+        let mut read = 0;
+        let head: Vec<u64> = simple8b_decode_iter(words.iter().inspect(|_| read += 1).copied())
+            .take(15)
+            .collect();
+        // values below 16 pack 15 per word
+        assert_eq!(head, values[..15]);
+        assert_eq!(read, 1);
+
+        let samples: Vec<TSSamples> = (0..100)
+            .map(|i| (i as f64 * 0.25, i as f64 * 0.5))
+            .collect();
+        let packed = TSPackSimple8bStrategy::pack(&samples, 1e-3);
+        assert_eq!(
+            TSPackSimple8bStrategy::iter_unpack(&packed, 1e-3).collect::<Vec<_>>(),
+            TSPackSimple8bStrategy::unpack(&packed, 1e-3)
+        );
+    }
 }
@@ -1,3 +1,6 @@
+use std::slice::Iter;
+
+use crate::columns::TSSampleSource;
 use crate::{TSPackedSamples, TSSamples};
 
 /// XOR-based (Gorilla-style) lossless compression for `f64` values.
@@ -8,19 +11,19 @@ use crate::{TSPackedSamples, TSSamples};
 pub struct TSPackXorGorillaStrategy;
 
 impl TSPackXorGorillaStrategy {
-    pub fn pack(samples: &[TSSamples]) -> Vec<TSPackedSamples> {
+    pub fn pack<S: TSSampleSource + ?Sized>(samples: &S) -> Vec<TSPackedSamples> {
         if samples.is_empty() {
             return Vec::new();
         }
 
         let mut packed = Vec::with_capacity(samples.len());
 
-        let (first_ts, first_val) = samples[0];
+        let (first_ts, first_val) = samples.sample(0);
         packed.push(((first_ts, first_ts), first_val));
 
         let mut prev_bits = first_val.to_bits();
 
-        for &(ts, val) in &samples[1..] {
+        for (ts, val) in samples.iter().skip(1) {
             let bits = val.to_bits();
             let xor_bits = prev_bits ^ bits;
             packed.push(((ts, ts), f64::from_bits(xor_bits)));
@@ -31,30 +34,49 @@ impl TSPackXorGorillaStrategy {
     }
 
     pub fn unpack(packed: &[TSPackedSamples]) -> Vec<TSSamples> {
-        if packed.is_empty() {
-            return Vec::new();
+        Self::iter_unpack(packed).collect()
+    }
+
+    /// Lazy [`Self::unpack`]: restores one sample per `next()` call.
+    pub fn iter_unpack(packed: &[TSPackedSamples]) -> TSPackXorGorillaUnpackIter<'_> {
+        TSPackXorGorillaUnpackIter {
+            packed: packed.iter(),
+            prev_bits: None,
         }
+    }
+}
+
+/// Iterator returned by [`TSPackXorGorillaStrategy::iter_unpack`].
+#[derive(Debug, Clone)]
+pub struct TSPackXorGorillaUnpackIter<'a> {
+    packed: Iter<'a, TSPackedSamples>,
+    prev_bits: Option<u64>,
+}
 
-        let mut samples = Vec::with_capacity(packed.len());
+impl Iterator for TSPackXorGorillaUnpackIter<'_> {
+    type Item = TSSamples;
 
-        let ((first_ts, _), first_val) = packed[0];
-        samples.push((first_ts, first_val));
+    fn next(&mut self) -> Option<TSSamples> {
+        let &((ts, _), xor_as_f64) = self.packed.next()?;
 
-        let mut prev_bits = first_val.to_bits();
+        let restored_bits = match self.prev_bits {
+            None => xor_as_f64.to_bits(),
+            Some(prev_bits) => prev_bits ^ xor_as_f64.to_bits(),
+        };
+        self.prev_bits = Some(restored_bits);
 
-        for &((ts, _), xor_as_f64) in &packed[1..] {
-            let restored_bits = prev_bits ^ xor_as_f64.to_bits();
-            let val = f64::from_bits(restored_bits);
-            samples.push((ts, val));
-            prev_bits = restored_bits;
-        }
+        Some((ts, f64::from_bits(restored_bits)))
+    }
 
-        samples
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.packed.size_hint()
     }
 }
 
+impl ExactSizeIterator for TSPackXorGorillaUnpackIter<'_> {}
+
 /// Convenience alias for [`TSPackXorGorillaStrategy::pack`].
-pub fn xor_pack(samples: &[TSSamples]) -> Vec<TSPackedSamples> {
+pub fn xor_pack<S: TSSampleSource + ?Sized>(samples: &S) -> Vec<TSPackedSamples> {
     TSPackXorGorillaStrategy::pack(samples)
 }
 
@@ -154,7 +176,7 @@ mod tests {
         };
 
         let packed = packer.pack(samples.clone(), attrs).unwrap();
-        let unpacked = TSPackXorGorillaStrategy::unpack(&packed);
+        let unpacked = TSPackXorGorillaStrategy::unpack(packed);
 
         assert_samples_eq(&samples, &unpacked);
     }
@@ -173,7 +195,7 @@ mod tests {
 
         // Simulate pipeline starting from raw samples; repack path is exercised internally.
         let repacked = packer.pack(samples.clone(), attrs).unwrap();
-        assert_samples_eq(&samples, &TSPackXorGorillaStrategy::unpack(&repacked));
+        assert_samples_eq(&samples, &TSPackXorGorillaStrategy::unpack(repacked));
         assert_eq!(first_pass.len(), repacked.len());
     }
 }
@@ -0,0 +1,22 @@
+//! Fixtures shared by the unit tests of the query modules.
+
+use crate::TSPackAttributes;
+use crate::TSPackStrategyType;
+use crate::TSSamples;
+use crate::TimeSeriesDataPacker;
+
+/// Packs `samples` with a single `strategy` over 1 s windows, epsilon 0.001.
+pub fn pack(samples: Vec<TSSamples>, strategy: TSPackStrategyType) -> TimeSeriesDataPacker {
+    let mut packer = TimeSeriesDataPacker::new();
+    packer
+        .pack(
+            samples,
+            TSPackAttributes {
+                strategy_types: vec![strategy],
+                microseconds_time_window: 1_000_000,
+                precision_epsilon: 0.001,
+            },
+        )
+        .unwrap();
+    packer
+}
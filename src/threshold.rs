@@ -0,0 +1,332 @@
+use crate::TSPackError;
+use crate::TimeSeriesDataPacker;
+
+/// Side of the level a [`TSPackThreshold`] alarms on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TSPackThresholdSide {
+    #[default]
+    Above,
+    Below,
+}
+
+/// Alarm level with hysteresis: an [`TSPackThresholdSide::Above`] event
+/// starts when a value exceeds `level` and ends only once a value drops to
+/// `level - hysteresis` or lower, so noise around the level does not flap.
+/// [`TSPackThresholdSide::Below`] mirrors it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSPackThreshold {
+    pub level: f64,
+    pub hysteresis: f64,
+    pub side: TSPackThresholdSide,
+}
+
+impl TSPackThreshold {
+    pub fn above(level: f64) -> Self {
+        Self {
+            level,
+            hysteresis: 0.0,
+            side: TSPackThresholdSide::Above,
+        }
+    }
+
+    pub fn below(level: f64) -> Self {
+        Self {
+            level,
+            hysteresis: 0.0,
+            side: TSPackThresholdSide::Below,
+        }
+    }
+
+    pub fn with_hysteresis(mut self, hysteresis: f64) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    fn enters(&self, value: f64) -> bool {
+        match self.side {
+            TSPackThresholdSide::Above => value > self.level,
+            TSPackThresholdSide::Below => value < self.level,
+        }
+    }
+
+    fn leaves(&self, value: f64) -> bool {
+        match self.side {
+            TSPackThresholdSide::Above => value <= self.level - self.hysteresis,
+            TSPackThresholdSide::Below => value >= self.level + self.hysteresis,
+        }
+    }
+
+    /// Whether `value` is further past the level than `extreme`.
+    fn more_extreme(&self, value: f64, extreme: f64) -> bool {
+        match self.side {
+            TSPackThresholdSide::Above => value > extreme,
+            TSPackThresholdSide::Below => value < extreme,
+        }
+    }
+}
+
+/// Interval `[start, end]` during which a [`TSPackThreshold`] was active,
+/// with the most extreme value seen in it (the maximum above the level, the
+/// minimum below it).
+///
+/// `clipped_start` is set when the event was already active at the query
+/// start, `clipped_end` when it was still active at the query end, at the
+/// end of the series or before a NaN gap: those edges are not crossings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSPackThresholdEvent {
+    pub start: f64,
+    pub end: f64,
+    pub extreme: f64,
+    pub clipped_start: bool,
+    pub clipped_end: bool,
+}
+
+impl TSPackThresholdEvent {
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+/// Whether a [`TSPackCrossing`] starts or ends a threshold event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TSPackCrossingKind {
+    Enter,
+    Leave,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSPackCrossing {
+    pub timestamp: f64,
+    pub kind: TSPackCrossingKind,
+}
+
+impl TimeSeriesDataPacker {
+    /// Intervals within `[start, end]` (seconds, inclusive) during which
+    /// `threshold` was active.
+    ///
+    /// Values follow step-hold like [`Self::aggregate`], so an event starts at
+    /// the first entry past the level and ends at the first entry that clears
+    /// the hysteresis band. Similar Values, Mean and Run-length ranges are
+    /// walked as whole `(start, end)` extents; bit-exact chains decode only
+    /// the windows overlapping the range. The state starts fresh at `start`,
+    /// from the value held there. A NaN sample ends the current event.
+    /// Fails with [`TSPackError::InvalidHysteresis`] for a negative or
+    /// non-finite hysteresis.
+    pub fn threshold_events(
+        &self,
+        start: f64,
+        end: f64,
+        threshold: TSPackThreshold,
+    ) -> Result<Vec<TSPackThresholdEvent>, TSPackError> {
+        if !threshold.hysteresis.is_finite() || threshold.hysteresis < 0.0 {
+            return Err(TSPackError::InvalidHysteresis(threshold.hysteresis));
+        }
+
+        let mut events = Vec::new();
+        if start.is_nan() || end.is_nan() || start > end {
+            return Ok(events);
+        }
+
+        let mut active: Option<TSPackThresholdEvent> = None;
+        let mut held_until = start;
+
+        for ((range_start, range_end), value) in self.entries_held_from(start) {
+            if range_start > end {
+                held_until = end;
+                break;
+            }
+
+            let ts = range_start.max(start);
+
+            match active.as_mut() {
+                Some(event) if value.is_nan() || threshold.leaves(value) => {
+                    event.end = ts;
+                    event.clipped_end = value.is_nan();
+                    events.push(*event);
+                    active = None;
+                }
+                Some(event) if threshold.more_extreme(value, event.extreme) => {
+                    event.extreme = value;
+                }
+                None if threshold.enters(value) => {
+                    active = Some(TSPackThresholdEvent {
+                        start: ts,
+                        end: ts,
+                        extreme: value,
+                        clipped_start: range_start < start,
+                        clipped_end: false,
+                    });
+                }
+                _ => {}
+            }
+
+            held_until = range_end.clamp(start, end);
+        }
+
+        if let Some(mut event) = active {
+            event.end = held_until;
+            event.clipped_end = true;
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+
+    /// Enter and leave timestamps of [`Self::threshold_events`], without the
+    /// clipped edges.
+    pub fn threshold_crossings(
+        &self,
+        start: f64,
+        end: f64,
+        threshold: TSPackThreshold,
+    ) -> Result<Vec<TSPackCrossing>, TSPackError> {
+        let mut crossings = Vec::new();
+
+        for event in self.threshold_events(start, end, threshold)? {
+            if !event.clipped_start {
+                crossings.push(TSPackCrossing {
+                    timestamp: event.start,
+                    kind: TSPackCrossingKind::Enter,
+                });
+            }
+            if !event.clipped_end {
+                crossings.push(TSPackCrossing {
+                    timestamp: event.end,
+                    kind: TSPackCrossingKind::Leave,
+                });
+            }
+        }
+
+        Ok(crossings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::pack;
+    use crate::{TSPackStrategyType, TSSamples};
+
+    // 10.0 except a noisy excursion around 20.0 between 3 s and 7 s
+    fn samples() -> Vec<TSSamples> {
+        [
+            10.0, 10.0, 10.0, 21.0, 19.5, 22.0, 19.0, 10.0, 10.0, 25.0, 25.0,
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (i as f64, *value))
+        .collect()
+    }
+
+    #[test]
+    fn hysteresis_suppresses_flapping() {
+        let packer = pack(samples(), TSPackStrategyType::TSPackXorStrategy);
+
+        let flapping = packer
+            .threshold_crossings(0.0, 10.0, TSPackThreshold::above(20.0))
+            .unwrap();
+        let kinds: Vec<(f64, TSPackCrossingKind)> =
+            flapping.iter().map(|c| (c.timestamp, c.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (3.0, TSPackCrossingKind::Enter),
+                (4.0, TSPackCrossingKind::Leave),
+                (5.0, TSPackCrossingKind::Enter),
+                (6.0, TSPackCrossingKind::Leave),
+                (9.0, TSPackCrossingKind::Enter),
+            ]
+        );
+
+        let events = packer
+            .threshold_events(0.0, 10.0, TSPackThreshold::above(20.0).with_hysteresis(2.0))
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                TSPackThresholdEvent {
+                    start: 3.0,
+                    end: 7.0,
+                    extreme: 22.0,
+                    clipped_start: false,
+                    clipped_end: false,
+                },
+                TSPackThresholdEvent {
+                    start: 9.0,
+                    end: 10.0,
+                    extreme: 25.0,
+                    clipped_start: false,
+                    clipped_end: true,
+                },
+            ]
+        );
+        assert_eq!(events[0].duration(), 4.0);
+    }
+
+    #[test]
+    fn ranges_are_walked_as_extents() {
+        // ((0, 2), 10.0), ((3, 8), 30.0), ((9, 9), 10.0)
+        let samples: Vec<TSSamples> = (0..10)
+            .map(|i| (i as f64, if (3..=8).contains(&i) { 30.0 } else { 10.0 }))
+            .collect();
+        let packer = pack(samples, TSPackStrategyType::TSPackRunLengthStrategy);
+        assert_eq!(packer.packed_samples().len(), 3);
+
+        let events = packer
+            .threshold_events(0.0, 20.0, TSPackThreshold::above(20.0))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].start, events[0].end), (3.0, 9.0));
+
+        // already above the level when the query starts, still above at its end
+        let clipped = packer
+            .threshold_events(4.5, 6.0, TSPackThreshold::above(20.0))
+            .unwrap();
+        assert_eq!(
+            clipped,
+            vec![TSPackThresholdEvent {
+                start: 4.5,
+                end: 6.0,
+                extreme: 30.0,
+                clipped_start: true,
+                clipped_end: true,
+            }]
+        );
+        assert_eq!(
+            packer
+                .threshold_crossings(4.5, 6.0, TSPackThreshold::above(20.0))
+                .unwrap(),
+            vec![]
+        );
+
+        let below = packer
+            .threshold_events(0.0, 20.0, TSPackThreshold::below(20.0))
+            .unwrap();
+        let bounds: Vec<(f64, f64)> = below.iter().map(|e| (e.start, e.end)).collect();
+        assert_eq!(bounds, vec![(0.0, 3.0), (9.0, 9.0)]);
+    }
+
+    #[test]
+    fn gaps_and_invalid_hysteresis() {
+        let samples = vec![(0.0, 30.0), (1.0, f64::NAN), (2.0, 30.0)];
+        let packer = pack(samples, TSPackStrategyType::TSPackXorStrategy);
+
+        let events = packer
+            .threshold_events(0.0, 2.0, TSPackThreshold::above(20.0))
+            .unwrap();
+        let bounds: Vec<(f64, f64, bool)> = events
+            .iter()
+            .map(|e| (e.start, e.end, e.clipped_end))
+            .collect();
+        assert_eq!(bounds, vec![(0.0, 1.0, true), (2.0, 2.0, true)]);
+
+        assert_eq!(
+            packer.threshold_events(0.0, 2.0, TSPackThreshold::above(20.0).with_hysteresis(-1.0)),
+            Err(TSPackError::InvalidHysteresis(-1.0))
+        );
+        assert_eq!(
+            packer.threshold_events(2.0, 0.0, TSPackThreshold::above(20.0)),
+            Ok(vec![])
+        );
+    }
+}
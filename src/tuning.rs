@@ -0,0 +1,253 @@
+use crate::helpers::uses_bit_exact_encoding;
+use crate::metrics::compute_error_metrics;
+use crate::metrics::TSPackAlignment;
+use crate::TSPackAttributes;
+use crate::TSPackError;
+use crate::TSPackStrategyType;
+use crate::TSSamples;
+use crate::TimeSeriesDataPacker;
+
+/// Reconstruction error a tuned pipeline must stay within, measured with
+/// [`compute_error_metrics`] on the series a reader gets back (step-hold
+/// aligned).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TSPackErrorBudget {
+    /// Largest absolute error of any sample, e.g. "max 0.05 °C".
+    MaxAbsError(f64),
+    /// Mean absolute error over all samples.
+    MeanAbsError(f64),
+}
+
+impl TSPackErrorBudget {
+    pub fn limit(&self) -> f64 {
+        match self {
+            TSPackErrorBudget::MaxAbsError(limit) | TSPackErrorBudget::MeanAbsError(limit) => {
+                *limit
+            }
+        }
+    }
+
+    /// Error of `reconstructed` against `original` in this budget's metric.
+    pub fn measure(&self, original: &[TSSamples], reconstructed: &[TSSamples]) -> f64 {
+        let metrics = compute_error_metrics(original, reconstructed, TSPackAlignment::StepHold);
+
+        match self {
+            TSPackErrorBudget::MaxAbsError(_) => metrics.max_error,
+            TSPackErrorBudget::MeanAbsError(_) => metrics.mae,
+        }
+    }
+}
+
+/// Values tried by [`tune_attributes`]. Percents only matter when the chain
+/// contains a mean strategy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TSPackTuneSpace {
+    pub precision_epsilons: Vec<f64>,
+    pub microseconds_time_windows: Vec<u64>,
+    pub values_compression_percents: Vec<u8>,
+}
+
+impl Default for TSPackTuneSpace {
+    fn default() -> Self {
+        Self {
+            precision_epsilons: (1..=9).map(|digits| 10_f64.powi(-digits)).collect(),
+            microseconds_time_windows: vec![
+                1_000, 10_000, 100_000, 1_000_000, 2_000_000, 5_000_000, 10_000_000, 60_000_000,
+            ],
+            values_compression_percents: vec![1, 2, 5, 10, 20, 30],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TSPackTuneResult {
+    pub attributes: TSPackAttributes,
+    pub packed_entries: usize,
+    pub error: f64,
+}
+
+fn with_percent(strategy_types: &[TSPackStrategyType], percent: u8) -> Vec<TSPackStrategyType> {
+    strategy_types
+        .iter()
+        .map(|strategy| match strategy {
+            TSPackStrategyType::TSPackMeanStrategy { .. } => {
+                TSPackStrategyType::TSPackMeanStrategy {
+                    values_compression_percent: percent,
+                }
+            }
+            other => other.clone(),
+        })
+        .collect()
+}
+
+/// Searches epsilon, window and (for mean strategies) the compression percent
+/// for the most compact attributes of `strategy_types` on `samples` whose
+/// reconstruction error stays within `budget`. Fewest packed entries wins;
+/// ties go to the smaller error.
+///
+/// Range chains are measured on [`TimeSeriesDataPacker::unpack`], whose values
+/// are rounded to `precision_epsilon` and held between range points; bit-exact
+/// chains on [`TimeSeriesDataPacker::decode`], since their packed payloads are
+/// not values.
+pub fn tune_attributes(
+    samples: &[TSSamples],
+    strategy_types: &[TSPackStrategyType],
+    budget: TSPackErrorBudget,
+    space: &TSPackTuneSpace,
+) -> Result<TSPackTuneResult, TSPackError> {
+    let limit = budget.limit();
+    if !limit.is_finite() || limit < 0.0 {
+        return Err(TSPackError::InvalidErrorBound(limit));
+    }
+
+    let has_mean = strategy_types
+        .iter()
+        .any(|strategy| matches!(strategy, TSPackStrategyType::TSPackMeanStrategy { .. }));
+    let chains: Vec<Vec<TSPackStrategyType>> = if has_mean {
+        space
+            .values_compression_percents
+            .iter()
+            .map(|percent| with_percent(strategy_types, *percent))
+            .collect()
+    } else {
+        vec![strategy_types.to_vec()]
+    };
+
+    let mut best: Option<TSPackTuneResult> = None;
+    let mut smallest_error = f64::INFINITY;
+
+    for chain in &chains {
+        for &microseconds_time_window in &space.microseconds_time_windows {
+            for &precision_epsilon in &space.precision_epsilons {
+                let attributes = TSPackAttributes {
+                    strategy_types: chain.clone(),
+                    microseconds_time_window,
+                    precision_epsilon,
+                };
+
+                TimeSeriesDataPacker::validate(samples, &attributes)?;
+
+                let mut packer = TimeSeriesDataPacker::new();
+                let packed_entries = packer.pack(samples.to_vec(), attributes.clone())?.len();
+                let reconstructed = if uses_bit_exact_encoding(chain) {
+                    packer.decode()
+                } else {
+                    packer.unpack().1
+                };
+                let error = budget.measure(samples, &reconstructed);
+                smallest_error = smallest_error.min(error);
+
+                if error > limit {
+                    continue;
+                }
+
+                let better = match &best {
+                    None => true,
+                    Some(current) => {
+                        packed_entries < current.packed_entries
+                            || (packed_entries == current.packed_entries && error < current.error)
+                    }
+                };
+
+                if better {
+                    best = Some(TSPackTuneResult {
+                        attributes,
+                        packed_entries,
+                        error,
+                    });
+                }
+            }
+        }
+    }
+
+    best.ok_or(TSPackError::ErrorBudgetExceeded {
+        error: smallest_error,
+        budget: limit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temperature_samples() -> Vec<TSSamples> {
+        (0..200)
+            .map(|i| {
+                let ts = i as f64 * 0.05;
+                (ts, 26.5 + (ts * 0.3).floor() * 0.3 + (i % 3) as f64 * 0.01)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_compact_attributes_within_max_error() {
+        let samples = temperature_samples();
+        let similar = [TSPackStrategyType::TSPackSimilarValuesStrategy];
+
+        let loose = tune_attributes(
+            &samples,
+            &similar,
+            TSPackErrorBudget::MaxAbsError(0.05),
+            &TSPackTuneSpace::default(),
+        )
+        .unwrap();
+        let strict = tune_attributes(
+            &samples,
+            &similar,
+            TSPackErrorBudget::MaxAbsError(1e-9),
+            &TSPackTuneSpace::default(),
+        )
+        .unwrap();
+
+        assert!(loose.error <= 0.05);
+        let mut packer = TimeSeriesDataPacker::new();
+        packer
+            .pack(samples.clone(), loose.attributes.clone())
+            .unwrap();
+        let (_, unpacked) = packer.unpack();
+        assert_eq!(
+            TSPackErrorBudget::MaxAbsError(0.05).measure(&samples, &unpacked),
+            loose.error
+        );
+        assert!(loose.packed_entries < strict.packed_entries);
+        assert!(loose.attributes.precision_epsilon > strict.attributes.precision_epsilon);
+    }
+
+    #[test]
+    fn tunes_mean_percent_for_mae_budget() {
+        let samples = temperature_samples();
+        let result = tune_attributes(
+            &samples,
+            &[TSPackStrategyType::TSPackMeanStrategy {
+                values_compression_percent: 0,
+            }],
+            TSPackErrorBudget::MeanAbsError(0.2),
+            &TSPackTuneSpace::default(),
+        )
+        .unwrap();
+
+        assert!(result.error <= 0.2);
+        assert!(result.packed_entries < samples.len() / 4);
+    }
+
+    #[test]
+    fn reports_unreachable_budget() {
+        let samples = vec![(0.0, 1.0), (0.5, 2.0)];
+        let space = TSPackTuneSpace {
+            precision_epsilons: vec![1e-3],
+            microseconds_time_windows: vec![1_000_000],
+            values_compression_percents: vec![50],
+        };
+        let mean = [TSPackStrategyType::TSPackMeanStrategy {
+            values_compression_percent: 50,
+        }];
+
+        assert_eq!(
+            tune_attributes(&samples, &mean, TSPackErrorBudget::MaxAbsError(0.1), &space),
+            Err(TSPackError::ErrorBudgetExceeded {
+                error: 0.5,
+                budget: 0.1,
+            })
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::columns::TSSampleSource;
+use crate::helpers::is_bit_exact;
+use crate::TSPackAttributes;
+use crate::TSPackError;
+use crate::TSPackStrategyType;
+
+/// XOR and run-length compare IEEE-754 bit patterns only, so NaN and
+/// infinities survive them. Every other strategy does arithmetic on values.
+#[inline]
+fn accepts_non_finite_values(strategies: &[TSPackStrategyType]) -> bool {
+    strategies.iter().all(|strategy| {
+        matches!(
+            strategy,
+            TSPackStrategyType::TSPackXorStrategy | TSPackStrategyType::TSPackRunLengthStrategy
+        )
+    })
+}
+
+pub fn validate_attributes(attributes: &TSPackAttributes) -> Result<(), TSPackError> {
+    if attributes.microseconds_time_window == 0 {
+        return Err(TSPackError::InvalidWindow);
+    }
+
+    let epsilon = attributes.precision_epsilon;
+    if !epsilon.is_finite() || epsilon < 0.0 {
+        return Err(TSPackError::InvalidEpsilon(epsilon));
+    }
+
+    if attributes.strategy_types.is_empty() {
+        return Err(TSPackError::EmptyStrategyChain);
+    }
+
+    let mut bit_exact_seen = false;
+
+    for (position, strategy) in attributes.strategy_types.iter().enumerate() {
+        if bit_exact_seen {
+            return Err(TSPackError::InvalidStrategyChain { position });
+        }
+
+        // increments of range values would not decode back to the counter
+        if position > 0 && *strategy == TSPackStrategyType::TSPackCounterStrategy {
+            return Err(TSPackError::InvalidStrategyChain { position });
+        }
+
+        if let TSPackStrategyType::TSPackMeanStrategy {
+            values_compression_percent,
+        } = strategy
+        {
+            if *values_compression_percent > 100 {
+                return Err(TSPackError::InvalidCompressionPercent {
+                    position,
+                    percent: *values_compression_percent,
+                });
+            }
+        }
+
+        if let TSPackStrategyType::TSPackAutoStrategy {
+            candidates,
+            max_error,
+        } = strategy
+        {
+            if !max_error.is_finite() || *max_error < 0.0 {
+                return Err(TSPackError::InvalidErrorBound(*max_error));
+            }
+            let nested = candidates.iter().any(|candidate| {
+                matches!(candidate, TSPackStrategyType::TSPackAutoStrategy { .. })
+            });
+            if candidates.is_empty() || nested {
+                return Err(TSPackError::InvalidAutoCandidates { position });
+            }
+            for candidate in candidates {
+                if let TSPackStrategyType::TSPackMeanStrategy {
+                    values_compression_percent,
+                } = candidate
+                {
+                    if *values_compression_percent > 100 {
+                        return Err(TSPackError::InvalidCompressionPercent {
+                            position,
+                            percent: *values_compression_percent,
+                        });
+                    }
+                }
+            }
+        }
+
+        bit_exact_seen = is_bit_exact(strategy);
+    }
+
+    Ok(())
+}
+
+pub fn validate_samples<S: TSSampleSource + ?Sized>(
+    samples: &S,
+    attributes: &TSPackAttributes,
+) -> Result<(), TSPackError> {
+    let check_values = !accepts_non_finite_values(&attributes.strategy_types);
+
+    for (index, (ts, value)) in samples.iter().enumerate() {
+        if !ts.is_finite() {
+            return Err(TSPackError::NonFiniteTimestamp { index });
+        }
+        if check_values && !value.is_finite() {
+            return Err(TSPackError::NonFiniteValue { index });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attrs(strategy_types: Vec<TSPackStrategyType>, precision_epsilon: f64) -> TSPackAttributes {
+        TSPackAttributes {
+            strategy_types,
+            microseconds_time_window: 1_000_000,
+            precision_epsilon,
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_epsilon() {
+        let similar = vec![TSPackStrategyType::TSPackSimilarValuesStrategy];
+
+        assert!(matches!(
+            validate_attributes(&attrs(similar.clone(), -1e-3)),
+            Err(TSPackError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            validate_attributes(&attrs(similar.clone(), f64::NAN)),
+            Err(TSPackError::InvalidEpsilon(_))
+        ));
+        assert_eq!(validate_attributes(&attrs(similar, 0.0)), Ok(()));
+    }
+
+    #[test]
+    fn rejects_invalid_chains() {
+        assert_eq!(
+            validate_attributes(&attrs(vec![], 1e-3)),
+            Err(TSPackError::EmptyStrategyChain)
+        );
+        assert_eq!(
+            validate_attributes(&attrs(
+                vec![
+                    TSPackStrategyType::TSPackSimilarValuesStrategy,
+                    TSPackStrategyType::TSPackMeanStrategy {
+                        values_compression_percent: 101,
+                    },
+                ],
+                1e-3
+            )),
+            Err(TSPackError::InvalidCompressionPercent {
+                position: 1,
+                percent: 101,
+            })
+        );
+        assert_eq!(
+            validate_attributes(&attrs(
+                vec![
+                    TSPackStrategyType::TSPackXorStrategy,
+                    TSPackStrategyType::TSPackSimilarValuesStrategy,
+                ],
+                1e-3
+            )),
+            Err(TSPackError::InvalidStrategyChain { position: 1 })
+        );
+        assert_eq!(
+            validate_attributes(&attrs(
+                vec![
+                    TSPackStrategyType::TSPackSimilarValuesStrategy,
+                    TSPackStrategyType::TSPackRunLengthStrategy,
+                ],
+                1e-3
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_non_finite_samples() {
+        let similar = attrs(vec![TSPackStrategyType::TSPackSimilarValuesStrategy], 1e-3);
+        let xor = attrs(vec![TSPackStrategyType::TSPackXorStrategy], 0.0);
+
+        assert_eq!(
+            validate_samples(&[(0.0, 1.0), (f64::INFINITY, 1.0)], &xor),
+            Err(TSPackError::NonFiniteTimestamp { index: 1 })
+        );
+        assert_eq!(
+            validate_samples(&[(0.0, 1.0), (0.1, f64::NAN)], &similar),
+            Err(TSPackError::NonFiniteValue { index: 1 })
+        );
+        assert_eq!(
+            validate_samples(&[(0.0, 1.0), (0.1, f64::NAN)], &xor),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+
+use crate::helpers::uses_bit_exact_encoding;
+use crate::metrics::value_at;
+use crate::metrics::TSPackAlignment;
+use crate::TSPackAttributes;
+use crate::TSPackError;
+use crate::TSSamples;
+use crate::TimeSeriesDataPacker;
+
+/// Largest reconstruction error of the samples that fall into one time window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSPackWindowVerification {
+    pub window_start: f64,
+    pub samples: usize,
+    pub max_error: f64,
+}
+
+/// An input sample whose reconstruction is further off than the bound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSPackViolation {
+    pub timestamp: f64,
+    pub value: f64,
+    pub reconstructed: f64,
+    pub error: f64,
+}
+
+/// Result of comparing the decoded series against its input.
+///
+/// Errors are step-hold aligned by timestamp. `bit_exact` is only set for
+/// chains with [`uses_bit_exact_encoding`]: `Some(true)` when every decoded
+/// timestamp and value matches the input bit for bit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TSPackVerifyReport {
+    pub bound: f64,
+    pub max_error: f64,
+    pub windows: Vec<TSPackWindowVerification>,
+    pub violations: Vec<TSPackViolation>,
+    pub bit_exact: Option<bool>,
+}
+
+impl TSPackVerifyReport {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl TimeSeriesDataPacker {
+    /// Packs like [`Self::pack`], then decodes the result and checks every
+    /// input sample against `max_error`. Fails with
+    /// [`TSPackError::VerificationFailed`], carrying the full report, when the
+    /// bound is breached; the packed series is kept either way.
+    pub fn pack_verified(
+        &mut self,
+        samples: Vec<TSSamples>,
+        attributes: TSPackAttributes,
+        max_error: f64,
+    ) -> Result<TSPackVerifyReport, TSPackError> {
+        if !max_error.is_finite() || max_error < 0.0 {
+            return Err(TSPackError::InvalidErrorBound(max_error));
+        }
+
+        let mut original = samples.clone();
+        self.pack(samples, attributes)?;
+
+        original.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        let report = self.verify(&original, max_error);
+
+        if report.passed() {
+            Ok(report)
+        } else {
+            Err(TSPackError::VerificationFailed {
+                report: Box::new(report),
+            })
+        }
+    }
+
+    /// Compares the decoded series against `original` (sorted by timestamp).
+    pub fn verify(&self, original: &[TSSamples], max_error: f64) -> TSPackVerifyReport {
+        let decoded = self.decode();
+
+        let mut windows: Vec<TSPackWindowVerification> = self
+            .window_starts
+            .iter()
+            .map(|&(window_start, _)| TSPackWindowVerification {
+                window_start,
+                samples: 0,
+                max_error: 0.0,
+            })
+            .collect();
+        let mut violations = Vec::new();
+        let mut overall: f64 = 0.0;
+
+        for &(timestamp, value) in original {
+            let (reconstructed, error) =
+                match value_at(&decoded, timestamp, TSPackAlignment::StepHold) {
+                    Some(held) if held.to_bits() == value.to_bits() => (held, 0.0),
+                    Some(held) => (held, (held - value).abs()),
+                    None => (f64::NAN, f64::INFINITY),
+                };
+
+            let window = self
+                .window_starts
+                .partition_point(|(start, _)| *start <= timestamp)
+                .saturating_sub(1);
+            if let Some(entry) = windows.get_mut(window) {
+                entry.samples += 1;
+                entry.max_error = entry.max_error.max(error);
+            }
+
+            overall = overall.max(error);
+            if error > max_error {
+                violations.push(TSPackViolation {
+                    timestamp,
+                    value,
+                    reconstructed,
+                    error,
+                });
+            }
+        }
+
+        let bit_exact = self
+            .attributes
+            .as_ref()
+            .filter(|attributes| uses_bit_exact_encoding(&attributes.strategy_types))
+            .map(|_| {
+                decoded.len() == original.len()
+                    && decoded.iter().zip(original).all(|(d, o)| {
+                        d.0.to_bits() == o.0.to_bits() && d.1.to_bits() == o.1.to_bits()
+                    })
+            });
+
+        TSPackVerifyReport {
+            bound: max_error,
+            max_error: overall,
+            windows,
+            violations,
+            bit_exact,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TSPackStrategyType;
+
+    fn attributes(strategy: TSPackStrategyType) -> TSPackAttributes {
+        TSPackAttributes {
+            strategy_types: vec![strategy],
+            microseconds_time_window: 1_000_000,
+            precision_epsilon: 0.1,
+        }
+    }
+
+    #[test]
+    fn lossless_chain_is_bit_exact() {
+        let samples = vec![(0.0, 1.25), (0.5, -3.5), (1.5, 7.125), (2.5, 0.1)];
+
+        let mut packer = TimeSeriesDataPacker::new();
+        let report = packer
+            .pack_verified(
+                samples,
+                attributes(TSPackStrategyType::TSPackXorStrategy),
+                0.0,
+            )
+            .unwrap();
+
+        assert_eq!(report.bit_exact, Some(true));
+        assert_eq!(report.max_error, 0.0);
+        assert_eq!(report.windows.len(), 2);
+        assert_eq!(report.windows[0].samples, 2);
+    }
+
+    #[test]
+    fn reports_windows_and_violations_of_lossy_chain() {
+        let samples = vec![(0.0, 1.0), (0.2, 1.05), (0.4, 1.0), (2.0, 5.0)];
+        let similar = attributes(TSPackStrategyType::TSPackSimilarValuesStrategy);
+
+        let mut packer = TimeSeriesDataPacker::new();
+        let report = packer
+            .pack_verified(samples.clone(), similar.clone(), 0.1)
+            .unwrap();
+        assert_eq!(report.bit_exact, None);
+        assert!((report.windows[0].max_error - 0.05).abs() < 1e-9);
+        assert_eq!(report.windows[1].max_error, 0.0);
+
+        let Err(TSPackError::VerificationFailed { report: failed }) =
+            packer.pack_verified(samples.clone(), similar, 0.01)
+        else {
+            panic!("expected a verification failure");
+        };
+        assert_eq!(failed.max_error, report.max_error);
+        assert_eq!(failed.bound, 0.01);
+        assert_eq!(failed.violations.len(), 1);
+        assert_eq!(failed.violations[0].timestamp, 0.2);
+        assert_eq!(failed.violations[0].reconstructed, 1.0);
+        assert_eq!(*failed, packer.verify(&samples, 0.01));
+    }
+}
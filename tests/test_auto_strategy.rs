@@ -0,0 +1,58 @@
+use time_series_data_packer_rs::{
+    TSPackAttributes, TSPackAutoStrategy, TSPackStrategyType, TSSamples, TimeSeriesDataPacker,
+};
+
+#[test]
+fn test_auto_strategy_records_choice_per_window() {
+    let samples: Vec<TSSamples> = vec![
+        (0.0, 0.0),
+        (0.1, 0.0),
+        (0.2, 0.0),
+        (0.3, 0.0),
+        (2.0, 1.25),
+        (2.1, -3.5),
+        (2.2, 7.0),
+    ];
+
+    let attrs: TSPackAttributes = "window=1s;eps=1e-3;auto(0,rle,xor)".parse().unwrap();
+
+    let mut packer = TimeSeriesDataPacker::new();
+    let packed = packer.pack(samples.clone(), attrs).unwrap();
+
+    assert_eq!(
+        TSPackAutoStrategy::chosen_strategies(packed),
+        vec![
+            TSPackStrategyType::TSPackRunLengthStrategy,
+            TSPackStrategyType::TSPackRunLengthStrategy,
+        ]
+    );
+
+    let (_attrs, unpacked) = packer.unpack();
+    assert_eq!(unpacked[0], (0.0, 0.0));
+    assert_eq!(unpacked[1], (0.3, 0.0));
+    assert_eq!(&unpacked[2..], &samples[4..]);
+}
+
+#[test]
+fn test_auto_strategy_stays_within_error_bound() {
+    let samples: Vec<TSSamples> = (0..200)
+        .map(|i| (i as f64 * 0.01, 20.0 + (i as f64 * 0.05).sin() * 0.02))
+        .collect();
+
+    let attrs: TSPackAttributes = "window=500ms;eps=1e-3;auto(0.01)".parse().unwrap();
+
+    let mut packer = TimeSeriesDataPacker::new();
+    let packed = packer.pack(samples.clone(), attrs).unwrap();
+    let recovered = TSPackAutoStrategy::unpack(packed, 1e-3);
+
+    assert!(packed.len() < samples.len());
+    for &(ts, value) in &samples {
+        let held = recovered
+            .iter()
+            .rev()
+            .find(|(rts, _)| *rts <= ts + 1e-9)
+            .map(|(_, v)| *v)
+            .unwrap();
+        assert!((held - value).abs() <= 0.01, "{ts}: {held} vs {value}");
+    }
+}
@@ -0,0 +1,98 @@
+use time_series_data_packer_rs::{
+    TSPackAttributes, TSPackCounterStrategy, TSPackError, TSPackStrategyType, TSSamples,
+    TimeSeriesDataPacker,
+};
+
+#[test]
+fn test_counter_strategy_roundtrip() {
+    let mut packer = TimeSeriesDataPacker::new();
+
+    // production counter resetting at 0.3 s
+    let samples: Vec<TSSamples> = vec![
+        (0.0, 40.0),
+        (0.1, 42.0),
+        (0.2, 45.0),
+        (0.3, 1.0),
+        (0.4, 4.0),
+    ];
+
+    let attrs = TSPackAttributes {
+        strategy_types: vec![TSPackStrategyType::TSPackCounterStrategy],
+        microseconds_time_window: 1_000_000,
+        precision_epsilon: 0.1,
+    };
+
+    let packed = packer.pack(samples.clone(), attrs).unwrap().to_vec();
+
+    assert_eq!(
+        packed,
+        vec![
+            ((0.0, 0.0), 40.0),
+            ((0.1, 0.1), 2.0),
+            ((0.2, 0.2), 3.0),
+            ((f64::NEG_INFINITY, f64::NEG_INFINITY), 45.0),
+            ((0.3, 0.3), 1.0),
+            ((0.4, 0.4), 3.0),
+        ]
+    );
+    assert_eq!(
+        TSPackCounterStrategy::resets(&packed, None),
+        vec![(0.3, 45.0)]
+    );
+    assert_eq!(packer.decode(), samples);
+    assert_eq!(TSPackCounterStrategy::unpack(&packed), samples);
+}
+
+#[test]
+fn test_counter_strategy_parses_from_spec() {
+    let strategy: TSPackStrategyType = "counter".parse().unwrap();
+
+    assert_eq!(strategy, TSPackStrategyType::TSPackCounterStrategy);
+    assert_eq!(strategy.to_string(), "counter");
+}
+
+#[test]
+fn test_counter_strategy_resets_between_windows() {
+    let mut packer = TimeSeriesDataPacker::new();
+
+    // the counter drops in the second window, which starts from a raw anchor
+    let samples: Vec<TSSamples> = vec![(0.0, 10.0), (0.5, 12.0), (1.2, 1.0)];
+
+    let attrs = TSPackAttributes {
+        strategy_types: vec![TSPackStrategyType::TSPackCounterStrategy],
+        microseconds_time_window: 1_000_000,
+        precision_epsilon: 0.1,
+    };
+
+    let packed = packer.pack(samples, attrs).unwrap();
+    let (first, second) = packed.split_at(2);
+
+    assert_eq!(TSPackCounterStrategy::resets(second, None), vec![]);
+    assert_eq!(
+        TSPackCounterStrategy::resets(second, Some(12.0)),
+        vec![(1.2, 12.0)]
+    );
+    assert_eq!(TSPackCounterStrategy::resets(first, None), vec![]);
+    assert_eq!(packer.counter_resets(0.0, 2.0), vec![1.2]);
+}
+
+#[test]
+fn test_counter_strategy_rejects_range_stages_before_it() {
+    let attrs = TSPackAttributes {
+        strategy_types: vec![
+            TSPackStrategyType::TSPackSimilarValuesStrategy,
+            TSPackStrategyType::TSPackCounterStrategy,
+        ],
+        microseconds_time_window: 1_000_000,
+        precision_epsilon: 0.1,
+    };
+
+    assert_eq!(
+        TimeSeriesDataPacker::validate(&[(0.0, 1.0)][..], &attrs),
+        Err(TSPackError::InvalidStrategyChain { position: 1 })
+    );
+    assert_eq!(
+        "similar|counter".parse::<TSPackAttributes>(),
+        Err(TSPackError::InvalidStrategyChain { position: 1 })
+    );
+}
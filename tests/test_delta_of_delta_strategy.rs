@@ -21,7 +21,7 @@ fn test_delta_of_delta_strategy_lossless_roundtrip() {
     };
 
     let packed = packer.pack(samples.clone(), attrs).unwrap();
-    let recovered = TSPackDeltaOfDeltaStrategy::unpack(&packed);
+    let recovered = TSPackDeltaOfDeltaStrategy::unpack(packed);
 
     assert_eq!(samples.len(), recovered.len());
     for (orig, rec) in samples.iter().zip(recovered.iter()) {
@@ -17,6 +17,7 @@ fn test_delta_strategy_roundtrip() {
     };
 
     let packed = packer.pack(samples.clone(), attrs.clone()).unwrap();
+    assert_eq!(packed.len(), samples.len());
     let (_attrs_back, unpacked) = packer.unpack();
 
     assert_eq!(samples.len(), unpacked.len());
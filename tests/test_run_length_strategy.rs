@@ -40,7 +40,7 @@ fn test_run_length_packer_unpack_matches_strategy_unpack() {
         precision_epsilon: 0.0,
     };
 
-    let packed = packer.pack(samples, attrs).unwrap();
+    let packed = packer.pack(samples, attrs).unwrap().to_vec();
     let (_attrs, from_packer) = packer.unpack();
     let from_strategy = TSPackRunLengthStrategy::unpack(&packed);
 
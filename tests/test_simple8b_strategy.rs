@@ -15,7 +15,7 @@ fn test_simple8b_strategy_approximate_roundtrip() {
     };
 
     let packed = packer.pack(samples.clone(), attrs.clone()).unwrap();
-    let recovered = TSPackSimple8bStrategy::unpack(&packed, attrs.precision_epsilon);
+    let recovered = TSPackSimple8bStrategy::unpack(packed, attrs.precision_epsilon);
 
     assert_eq!(samples.len(), recovered.len());
     for (orig, rec) in samples.iter().zip(recovered.iter()) {
@@ -35,7 +35,7 @@ fn test_simple8b_packer_unpack_returns_anchor_and_words() {
         precision_epsilon: TSPackPrecisionDataType::MilisValues.epsilon(),
     };
 
-    let packed = packer.pack(samples, attrs).unwrap();
+    let packed = packer.pack(samples, attrs).unwrap().to_vec();
     let (_attrs, from_packer) = packer.unpack();
 
     assert_eq!(from_packer[0], (0.0, 10.0));
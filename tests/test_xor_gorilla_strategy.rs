@@ -14,7 +14,7 @@ fn test_xor_gorilla_strategy_lossless_roundtrip() {
     };
 
     let packed = packer.pack(samples.clone(), attrs).unwrap();
-    let recovered = TSPackXorGorillaStrategy::unpack(&packed);
+    let recovered = TSPackXorGorillaStrategy::unpack(packed);
 
     assert_eq!(samples.len(), recovered.len());
     for (orig, rec) in samples.iter().zip(recovered.iter()) {
@@ -34,7 +34,7 @@ fn test_xor_gorilla_packer_unpack_returns_encoded_values() {
         precision_epsilon: 0.0,
     };
 
-    let packed = packer.pack(samples.clone(), attrs).unwrap();
+    let packed = packer.pack(samples.clone(), attrs).unwrap().to_vec();
     let (_attrs, encoded) = packer.unpack();
 
     assert_eq!(encoded.len(), samples.len());